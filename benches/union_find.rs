@@ -0,0 +1,87 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use disjoint_hash_set::{DisjointHashSet, UnionBy};
+use std::hint::black_box;
+
+/// Deterministic pseudo-random edges over `0..n`.
+fn edges(n: u64) -> Vec<(u64, u64)> {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % n
+    };
+    (0..n).map(|_| (next(), next())).collect()
+}
+
+fn union_find(c: &mut Criterion) {
+    for union_by in [UnionBy::Size, UnionBy::Rank] {
+        let mut group = c.benchmark_group(format!("union_find/{:?}", union_by));
+        for n in [1_000, 10_000, 100_000, 1_000_000] {
+            let edges = edges(n);
+            // per element cost should stay roughly flat as n grows
+            group.throughput(Throughput::Elements(n));
+
+            group.bench_with_input(BenchmarkId::new("union", n), &edges, |b, edges| {
+                b.iter(|| {
+                    let mut set = DisjointHashSet::<_>::with_union_by(union_by);
+                    for &(a, b) in edges {
+                        set.union(a, b);
+                    }
+                    set
+                })
+            });
+
+            let mut set = DisjointHashSet::<_>::with_union_by(union_by);
+            for &(a, b) in &edges {
+                set.union(a, b);
+            }
+            group.bench_with_input(BenchmarkId::new("find", n), &edges, |b, edges| {
+                b.iter(|| {
+                    for (a, _) in edges {
+                        black_box(set.find(a));
+                    }
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Building a set from edges in parallel against unioning them in a loop.
+#[cfg(feature = "rayon")]
+fn from_edges(c: &mut Criterion) {
+    use criterion::BatchSize;
+
+    let mut group = c.benchmark_group("from_edges");
+    group.sample_size(10);
+    for n in [100_000, 1_000_000] {
+        let edges = edges(n);
+        group.throughput(Throughput::Elements(n));
+
+        group.bench_with_input(BenchmarkId::new("union", n), &edges, |b, edges| {
+            b.iter(|| {
+                let mut set = DisjointHashSet::new();
+                for &(a, b) in edges {
+                    set.union(a, b);
+                }
+                set
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("from_edges_par", n), &edges, |b, edges| {
+            b.iter_batched(
+                || edges.clone(),
+                DisjointHashSet::from_edges_par,
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "rayon"))]
+fn from_edges(_: &mut Criterion) {}
+
+criterion_group!(benches, union_find, from_edges);
+criterion_main!(benches);
@@ -0,0 +1,439 @@
+use crate::disjoint_hash_set::DisjointHashSet;
+use crate::disjoint_set::Id;
+use dashmap::DashMap;
+use indexmap::IndexMap;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// log2 of the number of slots in the first bucket of `Parents`.
+const FIRST_BUCKET_BITS: u32 = 6;
+const BUCKETS: usize = (usize::BITS - FIRST_BUCKET_BITS) as usize;
+
+/// A growable array of atomic parent pointers.
+///
+/// Slots live in buckets that double in size and are never moved once
+/// allocated, so slots can be read and written while another thread grows
+/// the array.
+struct Parents {
+    buckets: [AtomicPtr<AtomicUsize>; BUCKETS],
+}
+
+impl Parents {
+    fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicPtr::new(ptr::null_mut())),
+        }
+    }
+
+    /// Returns the bucket a slot is in and its offset within that bucket.
+    fn locate(index: usize) -> (usize, usize) {
+        let pos = index + (1 << FIRST_BUCKET_BITS);
+        let bit = usize::BITS - 1 - pos.leading_zeros();
+        ((bit - FIRST_BUCKET_BITS) as usize, pos - (1 << bit))
+    }
+
+    fn bucket_len(bucket: usize) -> usize {
+        1 << (bucket as u32 + FIRST_BUCKET_BITS)
+    }
+
+    /// Returns a slot, which must already have been allocated with `alloc`.
+    fn get(&self, index: usize) -> &AtomicUsize {
+        let (bucket, offset) = Self::locate(index);
+        let slots = self.buckets[bucket].load(Ordering::Acquire);
+        assert!(!slots.is_null(), "slot {} was never allocated", index);
+        // SAFETY: a non-null bucket holds `bucket_len` slots and is only
+        // freed when `Parents` is dropped
+        unsafe { &*slots.add(offset) }
+    }
+
+    /// Allocate a slot, making it the root of a new set.
+    fn alloc(&self, index: usize) {
+        let (bucket, offset) = Self::locate(index);
+        let mut slots = self.buckets[bucket].load(Ordering::Acquire);
+        if slots.is_null() {
+            let new = Box::into_raw(
+                (0..Self::bucket_len(bucket))
+                    .map(|_| AtomicUsize::new(0))
+                    .collect::<Box<[AtomicUsize]>>(),
+            ) as *mut AtomicUsize;
+            slots = match self.buckets[bucket].compare_exchange(
+                ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => new,
+                Err(current) => {
+                    // another thread allocated the bucket first
+                    // SAFETY: `new` was never shared
+                    drop(unsafe { Self::free(new, bucket) });
+                    current
+                }
+            };
+        }
+        // SAFETY: as in `get`
+        unsafe { &*slots.add(offset) }.store(index, Ordering::Release);
+    }
+
+    /// Reclaim ownership of a bucket's slots.
+    ///
+    /// # Safety
+    /// `slots` must have been allocated by `alloc` for `bucket`, and must not
+    /// be used afterwards.
+    unsafe fn free(slots: *mut AtomicUsize, bucket: usize) -> Box<[AtomicUsize]> {
+        unsafe {
+            Box::from_raw(ptr::slice_from_raw_parts_mut(
+                slots,
+                Self::bucket_len(bucket),
+            ))
+        }
+    }
+}
+
+impl Drop for Parents {
+    fn drop(&mut self) {
+        for (bucket, slots) in self.buckets.iter_mut().enumerate() {
+            let slots = *slots.get_mut();
+            if !slots.is_null() {
+                // SAFETY: we have exclusive access, so nothing else uses the bucket
+                drop(unsafe { Self::free(slots, bucket) });
+            }
+        }
+    }
+}
+
+/// Roots are linked in the order of a fixed pseudo-random permutation of
+/// their ids, which keeps trees shallow in expectation without having to
+/// update sizes or ranks atomically alongside the parent pointers.
+fn priority(id: usize) -> u64 {
+    // multiplying by an odd constant is a bijection, so priorities never tie
+    (id as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+/// Find the root of an id in a forest of atomic parent pointers.
+pub(crate) fn root<'a>(parent: impl Fn(usize) -> &'a AtomicUsize, mut id: usize) -> usize {
+    // path halving, where losing a race just means another thread has
+    // already moved the node further up the same path
+    loop {
+        let up = parent(id).load(Ordering::Acquire);
+        if up == id {
+            return id;
+        }
+        let grandparent = parent(up).load(Ordering::Acquire);
+        if up != grandparent {
+            let _ = parent(id).compare_exchange_weak(
+                up,
+                grandparent,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            );
+        }
+        id = grandparent;
+    }
+}
+
+/// Union the sets of two ids in a forest of atomic parent pointers,
+/// returning `true` if they were merged.
+pub(crate) fn union<'a>(
+    parent: impl Fn(usize) -> &'a AtomicUsize + Copy,
+    mut value: usize,
+    mut other: usize,
+) -> bool {
+    loop {
+        value = root(parent, value);
+        other = root(parent, other);
+        if value == other {
+            return false;
+        }
+        let (child, root) = if priority(value) < priority(other) {
+            (value, other)
+        } else {
+            (other, value)
+        };
+        // fails if another thread linked `child` first, in which case
+        // find the new roots and try again
+        if parent(child)
+            .compare_exchange(child, root, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            return true;
+        }
+    }
+}
+
+/// A disjoint set that can be shared and updated by many threads at once.
+///
+/// Values are mapped to ids through a concurrent hash map, and sets are kept
+/// as trees of atomic parent pointers which are linked and compressed with
+/// compare-and-swap, so `find` and `union` never block on each other.
+///
+/// Values can't be split or removed; build the partition here and convert
+/// it to a [`DisjointHashSet`](crate::DisjointHashSet) for that.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+/// let set = ConcurrentDisjointHashSet::new();
+/// std::thread::scope(|s| {
+///     s.spawn(|| set.union(1, 2));
+///     s.spawn(|| set.union(3, 4));
+///     s.spawn(|| set.union(2, 3));
+/// });
+/// assert!(set.connected(&1, &4));
+/// assert_eq!(set.num_sets(), 1);
+/// ```
+pub struct ConcurrentDisjointHashSet<T: Hash + Eq, S = RandomState> {
+    ids: DashMap<T, usize, S>,
+    parents: Parents,
+    len: AtomicUsize,
+    num_sets: AtomicUsize,
+}
+
+impl<T: Hash + Eq> ConcurrentDisjointHashSet<T> {
+    /// Create an empty `ConcurrentDisjointHashSet`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create an empty `ConcurrentDisjointHashSet` with specified capacity.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_and_hasher(cap, RandomState::new())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Clone> ConcurrentDisjointHashSet<T, S> {
+    /// Create an empty `ConcurrentDisjointHashSet` which will use the given
+    /// hash builder to hash values.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(0, hash_builder)
+    }
+
+    /// Create an empty `ConcurrentDisjointHashSet` with specified capacity,
+    /// which will use the given hash builder to hash values.
+    pub fn with_capacity_and_hasher(cap: usize, hash_builder: S) -> Self {
+        Self {
+            ids: DashMap::with_capacity_and_hasher(cap, hash_builder),
+            parents: Parents::new(),
+            len: AtomicUsize::new(0),
+            num_sets: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of elements in the disjoint set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// set.insert("this");
+    /// assert_eq!(set.size(), 1);
+    /// ```
+    pub fn size(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of disjoint sets.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// set.union("this", "that");
+    /// set.insert("other");
+    /// assert_eq!(set.num_sets(), 2);
+    /// ```
+    pub fn num_sets(&self) -> usize {
+        self.num_sets.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if the disjoint set contains the specified value.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// set.insert("this");
+    /// assert!(set.contains(&"this"));
+    /// ```
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.ids.contains_key(value)
+    }
+
+    /// Insert a new value into the disjoint set.
+    ///
+    /// If the disjoint set already had this value present, returns `false`.
+    /// If not returns `true`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// assert!(set.insert("this"));
+    /// assert!(!set.insert("this"));
+    /// ```
+    pub fn insert(&self, value: T) -> bool {
+        self.insert_inner(value).1
+    }
+
+    fn insert_inner(&self, value: T) -> (usize, bool) {
+        if let Some(id) = self.ids.get(&value) {
+            return (*id, false);
+        }
+        let mut inserted = false;
+        let id = *self.ids.entry(value).or_insert_with(|| {
+            inserted = true;
+            let id = self.len.fetch_add(1, Ordering::AcqRel);
+            self.parents.alloc(id);
+            self.num_sets.fetch_add(1, Ordering::AcqRel);
+            id
+        });
+        (id, inserted)
+    }
+
+    /// Find the set a value is in.
+    ///
+    /// Returns `None` if the value is not present. Another thread may union
+    /// the set as soon as this returns, so the `Id` is only a snapshot.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// assert!(set.find(&"this").is_none());
+    /// set.union("this", "that");
+    /// assert_eq!(set.find(&"this"), set.find(&"that"));
+    /// ```
+    pub fn find<Q>(&self, value: &Q) -> Option<Id>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let id = *self.ids.get(value)?;
+        Some(Id(self.root(id)))
+    }
+
+    /// Find the set a value is in, inserting it if not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// let set_id = set.find_or_insert("this");
+    /// assert_eq!(set.find_or_insert("this"), set_id);
+    /// ```
+    pub fn find_or_insert(&self, value: T) -> Id {
+        let (id, _) = self.insert_inner(value);
+        Id(self.root(id))
+    }
+
+    fn parent(&self, id: usize) -> &AtomicUsize {
+        self.parents.get(id)
+    }
+
+    fn root(&self, id: usize) -> usize {
+        root(|id| self.parent(id), id)
+    }
+
+    /// Returns `true` if the two values are in the same set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// set.union("this", "that");
+    /// set.insert("other");
+    /// assert!(set.connected(&"this", &"that"));
+    /// assert!(!set.connected(&"this", &"other"));
+    /// ```
+    pub fn connected<Q>(&self, value: &Q, other: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (mut value, mut other) = match (self.ids.get(value), self.ids.get(other)) {
+            (Some(value), Some(other)) => (*value, *other),
+            _ => return false,
+        };
+        loop {
+            value = self.root(value);
+            other = self.root(other);
+            if value == other {
+                return true;
+            }
+            // the roots differed while `value` was still a root, otherwise
+            // it was linked concurrently and we look again
+            if self.parent(value).load(Ordering::Acquire) == value {
+                return false;
+            }
+        }
+    }
+
+    /// Unions two sets together specified by values, inserting them if not
+    /// present.
+    ///
+    /// Returns `true` if two sets were merged, or `false` if the values were
+    /// already in the same set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::ConcurrentDisjointHashSet;
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// assert!(set.union("this", "that"));
+    /// assert!(!set.union("that", "this"));
+    /// ```
+    pub fn union(&self, value: T, other: T) -> bool {
+        let (value, _) = self.insert_inner(value);
+        let (other, _) = self.insert_inner(other);
+        self.union_inner(value, other)
+    }
+
+    fn union_inner(&self, value: usize, other: usize) -> bool {
+        let merged = union(|id| self.parent(id), value, other);
+        if merged {
+            self.num_sets.fetch_sub(1, Ordering::AcqRel);
+        }
+        merged
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Clone> From<ConcurrentDisjointHashSet<T, S>>
+    for DisjointHashSet<T, S>
+{
+    /// Convert a `ConcurrentDisjointHashSet` once all threads are done with it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{ConcurrentDisjointHashSet, DisjointHashSet};
+    /// let set = ConcurrentDisjointHashSet::new();
+    /// set.union("this", "that");
+    /// let mut set = DisjointHashSet::from(set);
+    /// set.split("this");
+    /// assert!(!set.connected(&"this", &"that"));
+    /// ```
+    fn from(set: ConcurrentDisjointHashSet<T, S>) -> Self {
+        let roots: Vec<usize> = (0..set.size()).map(|id| set.root(id)).collect();
+
+        // ids were handed out contiguously, so ordering values by id lines
+        // them up with their nodes
+        let mut by_id: Vec<Option<T>> = roots.iter().map(|_| None).collect();
+        let mut values = IndexMap::with_capacity_and_hasher(roots.len(), set.ids.hasher().clone());
+        for (value, id) in set.ids {
+            by_id[id] = Some(value);
+        }
+        values.extend(by_id.into_iter().flatten().map(|value| (value, ())));
+        DisjointHashSet::from_roots(values, &roots)
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Clone + Default> Default for ConcurrentDisjointHashSet<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
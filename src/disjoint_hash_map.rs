@@ -0,0 +1,280 @@
+use crate::disjoint_hash_set::DisjointHashSet;
+use crate::disjoint_set::{DebugSet, Id, Union};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+/// Combines the data of two sets when a `DisjointHashMap` unions them.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::Merge;
+/// struct Count(usize);
+///
+/// impl Merge for Count {
+///     fn merge(self, other: Self) -> Self {
+///         Count(self.0 + other.0)
+///     }
+/// }
+/// ```
+pub trait Merge {
+    /// Combine the data of the two sets being unioned.
+    fn merge(self, other: Self) -> Self;
+}
+
+/// A disjoint set where every set carries associated data.
+///
+/// Each set's data stays attached to it as sets are unioned, being combined
+/// with [`Merge`] or a closure passed to `try_union_with`.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashMap;
+/// let mut map = DisjointHashMap::new();
+/// map.insert("this", 1);
+/// map.insert("that", 2);
+/// map.insert("other", 3);
+/// map.try_union_with(&"this", &"that", |a, b| a.min(b));
+/// assert_eq!(map.get(&"that"), Some(&1));
+/// assert_eq!(map.get(&"other"), Some(&3));
+/// ```
+pub struct DisjointHashMap<T: Hash + Eq, V, S = RandomState> {
+    set: DisjointHashSet<T, S>,
+    /// data of each set, stored at the index of its root
+    data: Vec<Option<V>>,
+}
+
+impl<T: Hash + Eq, V> DisjointHashMap<T, V> {
+    /// Create an empty `DisjointHashMap`.
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<T: Hash + Eq, V, S: BuildHasher> DisjointHashMap<T, V, S> {
+    /// Create an empty `DisjointHashMap` which will use the given hash
+    /// builder to hash values.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            set: DisjointHashSet::with_hasher(hash_builder),
+            data: Vec::new(),
+        }
+    }
+
+    /// Returns the number of elements in the disjoint map.
+    pub fn size(&self) -> usize {
+        self.set.size()
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn num_sets(&self) -> usize {
+        self.set.num_sets()
+    }
+
+    /// Returns `true` if the disjoint map contains the specified value.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.set.contains(value)
+    }
+
+    /// Find the set a value is in.
+    ///
+    /// Returns `None` if the value is not present.
+    pub fn find<Q>(&mut self, value: &Q) -> Option<Id>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.set.find(value)
+    }
+
+    /// Returns `true` if the two values are in the same set.
+    pub fn connected<Q>(&mut self, value: &Q, other: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.set.connected(value, other)
+    }
+
+    /// Insert a value into its own set with associated data.
+    ///
+    /// If the value is already present it stays in its set, and the data of
+    /// that whole set is replaced, returning the old data.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashMap;
+    /// let mut map = DisjointHashMap::new();
+    /// assert_eq!(map.insert("this", 1), None);
+    /// assert_eq!(map.insert("this", 2), Some(1));
+    /// assert_eq!(map.get(&"this"), Some(&2));
+    ///
+    /// map.insert("that", 3);
+    /// map.try_union_with(&"this", &"that", |a, b| a + b);
+    /// assert_eq!(map.insert("this", 10), Some(5));
+    /// assert_eq!(map.get(&"that"), Some(&10));
+    /// ```
+    pub fn insert(&mut self, value: T, data: V) -> Option<V> {
+        let size = self.set.size();
+        let root = self.set.find_or_insert(value);
+        if self.set.size() > size {
+            self.data.push(Some(data));
+            None
+        } else {
+            self.data[root.0].replace(data)
+        }
+    }
+
+    /// Returns the data of the set a value is in.
+    pub fn get<Q>(&self, value: &Q) -> Option<&V>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let root = self.set.find_ref(value)?;
+        self.data[root.0].as_ref()
+    }
+
+    /// Returns a mutable reference to the data of the set a value is in.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashMap;
+    /// let mut map = DisjointHashMap::new();
+    /// map.insert("this", vec!["this"]);
+    /// map.get_mut(&"this").unwrap().push("that");
+    /// assert_eq!(map.get(&"this"), Some(&vec!["this", "that"]));
+    /// ```
+    pub fn get_mut<Q>(&mut self, value: &Q) -> Option<&mut V>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let root = self.set.find(value)?;
+        self.data[root.0].as_mut()
+    }
+
+    /// Unions the sets of two values, merging their data with [`Merge`].
+    ///
+    /// Returns whether the sets were merged or were already the same set.
+    /// Returns `None`, doing nothing, if either value is not present, like
+    /// [`DisjointHashSet::try_union`](crate::DisjointHashSet::try_union).
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointHashMap, Merge};
+    /// #[derive(Debug, PartialEq)]
+    /// struct Count(usize);
+    ///
+    /// impl Merge for Count {
+    ///     fn merge(self, other: Self) -> Self {
+    ///         Count(self.0 + other.0)
+    ///     }
+    /// }
+    ///
+    /// let mut map = DisjointHashMap::new();
+    /// map.insert("this", Count(1));
+    /// map.insert("that", Count(1));
+    /// assert!(map.try_union(&"this", &"that").unwrap().is_merged());
+    /// assert_eq!(map.get(&"this"), Some(&Count(2)));
+    /// ```
+    pub fn try_union<Q>(&mut self, value: &Q, other: &Q) -> Option<Union>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        V: Merge,
+    {
+        self.try_union_with(value, other, V::merge)
+    }
+
+    /// Unions the sets of two values, merging their data with a closure.
+    ///
+    /// The closure is given the data of `value`'s set followed by the data of
+    /// `other`'s set, and isn't called if they are already the same set.
+    /// Returns whether the sets were merged, or `None`, doing nothing, if
+    /// either value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashMap;
+    /// let mut map = DisjointHashMap::new();
+    /// map.insert("this", 2);
+    /// map.insert("that", 3);
+    /// assert!(map.try_union_with(&"this", &"that", |a, b| a * b).unwrap().is_merged());
+    /// assert!(!map.try_union_with(&"this", &"that", |a, b| a * b).unwrap().is_merged());
+    /// assert!(map.try_union_with(&"this", &"other", |a, b| a * b).is_none());
+    /// assert_eq!(map.get(&"that"), Some(&6));
+    /// ```
+    pub fn try_union_with<Q, F>(&mut self, value: &Q, other: &Q, merge: F) -> Option<Union>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        F: FnOnce(V, V) -> V,
+    {
+        let value = self.set.find(value)?;
+        let other = self.set.find(other)?;
+        let union = self.set.union_inner(value, other);
+        if let Union::Merged { root, .. } = union {
+            let value = self.data[value.0].take();
+            let other = self.data[other.0].take();
+            if let (Some(value), Some(other)) = (value, other) {
+                self.data[root.0] = Some(merge(value, other));
+            }
+        }
+        Some(union)
+    }
+
+    /// Returns an iterator over the disjoint sets, each collected into a
+    /// `Vec` of references to its values, along with its data.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashMap;
+    /// let mut map = DisjointHashMap::new();
+    /// map.insert("this", 1);
+    /// map.insert("that", 2);
+    /// map.try_union_with(&"this", &"that", |a, b| a + b);
+    /// let sets: Vec<_> = map.sets().map(|(set, data)| (set.len(), *data)).collect();
+    /// assert_eq!(sets, vec![(2, 3)]);
+    /// ```
+    pub fn sets(&self) -> impl Iterator<Item = (Vec<&T>, &V)> {
+        self.data.iter().enumerate().filter_map(move |(id, data)| {
+            Some((self.set.members_of(Id(id)).collect(), data.as_ref()?))
+        })
+    }
+}
+
+/// Formats the map as its sets with their data, like `{{"this", "that"}: 3}`.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashMap;
+/// let mut map = DisjointHashMap::new();
+/// map.insert("this", 1);
+/// assert_eq!(format!("{:?}", map), r#"{{"this"}: 1}"#);
+/// ```
+impl<T, V, S> fmt::Debug for DisjointHashMap<T, V, S>
+where
+    T: Hash + Eq + fmt::Debug,
+    V: fmt::Debug,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for (set, data) in self.sets() {
+            map.entry(&DebugSet(&set), data);
+        }
+        map.finish()
+    }
+}
+
+impl<T: Hash + Eq, V, S: BuildHasher + Default> Default for DisjointHashMap<T, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
@@ -1,42 +1,75 @@
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use crate::disjoint_set::{DebugSet, DisjointSet, Id, IndexType, Ring, Union, UnionBy};
+use indexmap::{map, IndexMap};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
 use std::iter::IntoIterator;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub struct Id(usize);
-
-#[derive(Copy, Clone, PartialEq, Debug)]
-struct Node {
-    size: usize,
-    parent: Id,
-}
-
-impl Node {
-    fn new(parent: Id) -> Self {
-        Self { size: 1, parent }
-    }
-}
-
-#[derive(Debug)]
-pub struct DisjointHashSet<T: Hash + Eq> {
-    map: HashMap<T, Id>,
-    data: Vec<Node>,
+/// A disjoint set of hashable values.
+///
+/// Values are hashed with `S` and the sets are kept in a [`DisjointSet`]
+/// indexed by `I`. With `u32` the sets take 8 bytes a value, 9 when unioning
+/// by rank, against 16 for `usize`; see [`IndexType`] for the limits. The
+/// values themselves are kept in an `IndexMap`, which adds an 8 byte hash
+/// and an 8 byte index to every value whatever `I` is.
+///
+/// `new` and `with_capacity` create sets indexed by `usize`; every other
+/// constructor builds any index type. Methods that insert values panic once
+/// `I` has no room for another.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashSet;
+/// use std::collections::hash_map::RandomState;
+///
+/// let mut set = DisjointHashSet::<u64, RandomState, u32>::with_capacity_and_hasher(
+///     16,
+///     RandomState::new(),
+/// );
+/// set.union(1, 2);
+/// assert!(set.connected(&1, &2));
+/// ```
+#[derive(Clone)]
+pub struct DisjointHashSet<T: Hash + Eq, S = RandomState, I = usize> {
+    /// values by id, in a map rather than a set for its raw entry api
+    pub(crate) values: IndexMap<T, (), S>,
+    /// the sets of the values, by the index of each value
+    pub(crate) set: DisjointSet<I>,
 }
 
 impl<T: Hash + Eq> DisjointHashSet<T> {
     /// Create an empty `DisjointHashSet`.
     pub fn new() -> Self {
         Self {
-            map: HashMap::new(),
-            data: Vec::new(),
+            values: IndexMap::new(),
+            set: DisjointSet::new(),
         }
     }
 
     /// Create an empty `DisjointHashSet` with specified capacity.
     pub fn with_capacity(cap: usize) -> Self {
         Self {
-            map: HashMap::with_capacity(cap),
-            data: Vec::with_capacity(cap),
+            values: IndexMap::with_capacity(cap),
+            set: DisjointSet::with_capacity(cap),
+        }
+    }
+}
+
+impl<T: Hash + Eq, I: IndexType> DisjointHashSet<T, RandomState, I> {
+    /// Create an empty `DisjointHashSet` using the specified union strategy.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointHashSet, UnionBy};
+    /// let mut set = DisjointHashSet::<&str>::with_union_by(UnionBy::Rank);
+    /// set.union("this", "that");
+    /// assert!(set.connected(&"this", &"that"));
+    /// ```
+    pub fn with_union_by(union_by: UnionBy) -> Self {
+        Self {
+            values: IndexMap::new(),
+            set: DisjointSet::with_union_by(union_by),
         }
     }
 
@@ -45,7 +78,7 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// # Examples
     /// ```
     /// # use disjoint_hash_set::DisjointHashSet;
-    /// let mut set = DisjointHashSet::with_values(vec!["this", "that", "other"]);
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that", "other"]);
     /// assert!(set.find(&"this").is_some());
     /// assert!(set.find(&"that").is_some());
     /// assert!(set.find(&"other").is_some());
@@ -54,13 +87,71 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     where
         S: IntoIterator<Item = T>,
     {
-        let map: HashMap<T, Id> = set
-            .into_iter()
-            .enumerate()
-            .map(|(a, b)| (b, Id(a)))
-            .collect();
-        let data = (0..map.len()).map(|i| Node::new(Id(i))).collect();
-        Self { map, data }
+        let values: IndexMap<T, ()> = set.into_iter().map(|value| (value, ())).collect();
+        Self {
+            set: DisjointSet::with_size(values.len()),
+            values,
+        }
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher, I: IndexType> DisjointHashSet<T, S, I> {
+    /// Create an empty `DisjointHashSet` which will use the given hash builder
+    /// to hash values.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    ///
+    /// let mut set = DisjointHashSet::<&str, _>::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+    /// set.union("this", "that");
+    /// assert!(set.connected(&"this", &"that"));
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            values: IndexMap::with_hasher(hash_builder),
+            set: DisjointSet::new(),
+        }
+    }
+
+    /// Create an empty `DisjointHashSet` using the specified union strategy,
+    /// which will use the given hash builder to hash values.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointHashSet, UnionBy};
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    ///
+    /// let hash_builder = BuildHasherDefault::<DefaultHasher>::default();
+    /// let mut set = DisjointHashSet::<&str, _>::with_hasher_and_union_by(hash_builder, UnionBy::Rank);
+    /// set.union("this", "that");
+    /// assert!(set.connected(&"this", &"that"));
+    /// ```
+    pub fn with_hasher_and_union_by(hash_builder: S, union_by: UnionBy) -> Self {
+        Self {
+            values: IndexMap::with_hasher(hash_builder),
+            set: DisjointSet::with_union_by(union_by),
+        }
+    }
+
+    /// Create an empty `DisjointHashSet` with specified capacity, which will
+    /// use the given hash builder to hash values.
+    pub fn with_capacity_and_hasher(cap: usize, hash_builder: S) -> Self {
+        Self {
+            values: IndexMap::with_capacity_and_hasher(cap, hash_builder),
+            set: DisjointSet::with_capacity(cap),
+        }
+    }
+
+    /// Build a disjoint set from values in id order and the root of each id.
+    pub(crate) fn from_roots(values: IndexMap<T, (), S>, roots: &[usize]) -> Self {
+        Self {
+            values,
+            set: DisjointSet::from_roots(roots),
+        }
     }
 
     /// Returns the number of elements in the disjoint set.
@@ -74,7 +165,21 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// assert_eq!(set.size(), 1);
     /// ```
     pub fn size(&self) -> usize {
-        self.data.len()
+        self.set.size()
+    }
+
+    /// Returns the number of disjoint sets.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that", "other"]);
+    /// assert_eq!(set.num_sets(), 3);
+    /// set.union("this", "that");
+    /// assert_eq!(set.num_sets(), 2);
+    /// ```
+    pub fn num_sets(&self) -> usize {
+        self.set.num_sets()
     }
 
     /// Returns `true` if the disjoint set contains the specified value.
@@ -86,22 +191,57 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// set.insert("this");
     /// assert!(set.contains(&"this"));
     /// ```
-    pub fn contains(&self, value: &T) -> bool {
-        self.map.contains_key(value)
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.values.contains_key(value)
     }
 
     /// Returns `true` if the two values are in the same set.
     ///
+    /// Returns `false` if either value is not present, even if neither is.
+    ///
     /// # Examples
     /// ```
     /// # use disjoint_hash_set::DisjointHashSet;
-    /// let mut set = DisjointHashSet::with_values(vec!["this", "that"]);
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that"]);
     /// assert!(!set.connected(&"this", &"that"));
     /// set.union("this", "that");
     /// assert!(set.connected(&"this", &"that"));
+    /// assert!(!set.connected(&"other", &"another"));
+    /// ```
+    pub fn connected<Q>(&mut self, value: &Q, other: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let value = self.find(value);
+        value.is_some() && value == self.find(other)
+    }
+
+    /// Returns `true` if the two values are in the same set, without
+    /// compressing the paths to their roots.
+    ///
+    /// Returns `false` if either value is not present, even if neither is.
+    ///
+    /// # Examples
     /// ```
-    pub fn connected(&mut self, value: &T, other: &T) -> bool {
-        self.find(value) == self.find(other)
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that"]);
+    /// set.union("this", "that");
+    /// let set = &set;
+    /// assert!(set.connected_ref(&"this", &"that"));
+    /// assert!(!set.connected_ref(&"other", &"another"));
+    /// ```
+    pub fn connected_ref<Q>(&self, value: &Q, other: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let value = self.find_ref(value);
+        value.is_some() && value == self.find_ref(other)
     }
 
     /// Insert a new value into the disjoint set.
@@ -109,27 +249,51 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// If the disjoint set already had this value present, returns `false`.
     /// If not returns `true`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the value is new and the index type `I` has no room for
+    /// another value. The set is left unchanged.
+    ///
     /// # Examples
     /// ```
     /// # use disjoint_hash_set::DisjointHashSet;
     /// let mut set = DisjointHashSet::<&str>::new();
     /// assert!(!set.contains(&"this"));
-    /// set.insert("this");
+    /// assert!(set.insert("this"));
     /// assert!(set.contains(&"this"));
+    /// assert!(!set.insert("this"));
     /// ```
     pub fn insert(&mut self, value: T) -> bool {
-        self.insert_inner(value) != Id(self.size() - 1)
+        self.insert_full(value).1
+    }
+
+    /// Insert a new value into the disjoint set, returning the id of its set
+    /// along with whether it was newly inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::new();
+    /// let (id, inserted) = set.insert_full("this");
+    /// assert!(inserted);
+    /// assert_eq!(set.insert_full("this"), (id, false));
+    /// ```
+    pub fn insert_full(&mut self, value: T) -> (Id, bool) {
+        let size = self.size();
+        let id = self.insert_inner(value);
+        (self.compress_path(id), self.size() > size)
     }
 
     fn insert_inner(&mut self, value: T) -> Id {
-        self.map
-            .entry(value)
-            .or_insert_with(|| {
-                let new_id = Id(self.data.len());
-                self.data.push(Node::new(new_id));
-                new_id
-            })
-            .clone()
+        match self.values.entry(value) {
+            map::Entry::Occupied(entry) => Id(entry.index()),
+            map::Entry::Vacant(entry) => {
+                // check before adding the value, so a full set is left as is
+                self.set.assert_room();
+                entry.insert(());
+                self.set.push_node()
+            }
+        }
     }
 
     /// Insert a connected set into the disjoint set.
@@ -143,9 +307,9 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// set.insert_set(vec!["this", "that"]);
     /// assert!(set.connected(&"this", &"that"));
     /// ```
-    pub fn insert_set<S>(&mut self, set: S)
+    pub fn insert_set<V>(&mut self, set: V)
     where
-        S: IntoIterator<Item = T>,
+        V: IntoIterator<Item = T>,
     {
         let mut set = set.into_iter();
         let mut k = match set.next() {
@@ -155,20 +319,15 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
         set.for_each(|v| {
             let v = self.find_or_insert(v);
             k = self.compress_path(k);
-            self.union_inner(k, v)
+            self.union_inner(k, v);
         });
     }
 
-    fn get(&self, id: Id) -> Node {
-        self.data[id.0]
-    }
-
-    fn get_mut(&mut self, id: Id) -> &mut Node {
-        &mut self.data[id.0]
-    }
-
     /// Find the set a value is in.
     ///
+    /// The value may be any borrowed form of the set's value type, but `Hash`
+    /// and `Eq` on the borrowed form must match those for the value type.
+    ///
     /// Returns `None` if the value is not present.
     ///
     /// # Examples
@@ -178,12 +337,89 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// assert!(set.find(&"this").is_none());
     /// set.insert("this");
     /// assert!(set.find(&"this").is_some());
+    ///
+    /// let mut set = DisjointHashSet::<String>::new();
+    /// set.insert("this".to_string());
+    /// assert!(set.find("this").is_some());
     /// ```
-    pub fn find(&mut self, value: &T) -> Option<Id> {
-        let id = self.map.get(value)?.clone();
+    pub fn find<Q>(&mut self, value: &Q) -> Option<Id>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let id = Id(self.values.get_index_of(value)?);
         Some(self.compress_path(id))
     }
 
+    /// Find the set a value is in without compressing the path to it.
+    ///
+    /// Unlike `find` this only needs a shared reference, so it can be used
+    /// on a disjoint set that is shared, at the cost of not speeding up later
+    /// lookups.
+    ///
+    /// Returns `None` if the value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::new();
+    /// let id = set.find_or_insert("this");
+    /// let set = &set;
+    /// assert_eq!(set.find_ref(&"this"), Some(id));
+    /// assert!(set.find_ref(&"that").is_none());
+    /// ```
+    pub fn find_ref<Q>(&self, value: &Q) -> Option<Id>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let id = Id(self.values.get_index_of(value)?);
+        Some(self.set.root(id))
+    }
+
+    /// Returns the representative value of the set a value is in, the value
+    /// at the set's root.
+    ///
+    /// The representative is the same for every value of a set, so it can
+    /// serve as a label for the set, until sets are next changed.
+    ///
+    /// Returns `None` if the value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::new();
+    /// set.insert_set(vec!["this", "that"]);
+    /// set.insert("other");
+    /// assert_eq!(set.representative(&"this"), set.representative(&"that"));
+    /// assert_eq!(set.representative(&"other"), Some(&"other"));
+    /// assert_eq!(set.representative(&"another"), None);
+    /// ```
+    pub fn representative<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.value_of(self.find_ref(value)?)
+    }
+
+    /// Returns the value with the specified id, such as the root value of a
+    /// set.
+    ///
+    /// Returns `None` if the id is out of bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::new();
+    /// let id = set.union("this", "that").root();
+    /// let root = set.value_of(id).unwrap();
+    /// assert!(*root == "this" || *root == "that");
+    /// ```
+    pub fn value_of(&self, id: Id) -> Option<&T> {
+        self.values.get_index(id.0).map(|(value, ())| value)
+    }
+
     /// Find the set a value is in, inserting it if not present.
     ///
     /// # Examples
@@ -198,31 +434,32 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
         self.compress_path(id)
     }
 
-    fn compress_path(&mut self, mut id: Id) -> Id {
-        // path halving
-        let mut parent = self.get(id).parent;
-        while parent != id {
-            self.get_mut(id).parent = self.get(parent).parent;
-            id = parent;
-            parent = self.get(id).parent;
-        }
-        id
+    pub(crate) fn compress_path(&mut self, id: Id) -> Id {
+        self.set.compress_path(id)
     }
 
     /// Unions two sets together specified by values.
     ///
+    /// Returns whether the sets were merged or were already the same set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value is new and the index type `I` has no room for it.
+    ///
     /// # Examples
     /// ```
-    /// # use disjoint_hash_set::DisjointHashSet;
-    /// let mut set = DisjointHashSet::with_values(vec!["this", "that"]);
-    /// set.union(&"this", &"that");
+    /// # use disjoint_hash_set::{DisjointHashSet, Union};
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that"]);
+    /// assert!(set.union("this", "that").is_merged());
     /// assert!(set.connected(&"this", &"that"));
+    /// let root = set.find(&"this").unwrap();
+    /// assert_eq!(set.union("that", "this"), Union::AlreadyConnected { root });
     /// ```
-    pub fn union(&mut self, value: T, other: T) {
+    pub fn union(&mut self, value: T, other: T) -> Union {
         let value = self.find_or_insert(value);
         let other = self.find_or_insert(other);
 
-        self.union_inner(value, other);
+        self.union_inner(value, other)
     }
 
     /// Union two sets together by their set id's.
@@ -236,34 +473,88 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// set.union_sets(id_1, id_2);
     /// assert!(set.connected(&"this", &"that"));
     /// ```
-    pub fn union_sets(&mut self, value: Id, other: Id) {
+    pub fn union_sets(&mut self, value: Id, other: Id) -> Union {
         let value = self.compress_path(value);
         let other = self.compress_path(other);
 
-        self.union_inner(value, other);
+        self.union_inner(value, other)
+    }
+
+    /// Union the sets of two values that are already present.
+    ///
+    /// Unlike `union` the values may be borrowed forms of the value type.
+    /// Returns `None`, doing nothing, if either value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<String>::with_values(vec!["this".to_string(), "that".to_string()]);
+    /// assert!(set.try_union("this", "that").is_some());
+    /// assert!(set.connected("this", "that"));
+    /// assert!(set.try_union("this", "other").is_none());
+    /// ```
+    pub fn try_union<Q>(&mut self, value: &Q, other: &Q) -> Option<Union>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let value = self.find(value)?;
+        let other = self.find(other)?;
+        Some(self.union_inner(value, other))
     }
 
     /// value and other are assumed to be the root
-    fn union_inner(&mut self, value_id: Id, other_id: Id) {
-        let value = self.get(value_id);
-        let other = self.get(other_id);
-        if value == other {
-            return;
-        }
+    pub(crate) fn union_inner(&mut self, value: Id, other: Id) -> Union {
+        self.set.union_inner(value, other)
+    }
 
-        if value.size < other.size {
-            self.get_mut(other_id).parent = value.parent;
-            self.get_mut(value_id).size += other.size;
-        } else {
-            self.get_mut(value_id).parent = other.parent;
-            self.get_mut(other_id).size += value.size;
-        }
+    /// Returns the number of values in the same set as a value.
+    ///
+    /// Returns `None` if the value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::new();
+    /// assert_eq!(set.set_size(&"this"), None);
+    /// set.insert_set(vec!["this", "that"]);
+    /// assert_eq!(set.set_size(&"this"), Some(2));
+    /// set.split("that");
+    /// assert_eq!(set.set_size(&"this"), Some(1));
+    /// ```
+    pub fn set_size<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let id = Id(self.values.get_index_of(value)?);
+        Some(self.set_size_of(id))
+    }
+
+    /// Returns the number of values in the set specified by it's id.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::new();
+    /// set.insert_set(vec!["this", "that"]);
+    /// let id = set.find_or_insert("other");
+    /// assert_eq!(set.set_size_of(id), 1);
+    /// set.split_into_set("this", id);
+    /// assert_eq!(set.set_size_of(id), 2);
+    /// ```
+    pub fn set_size_of(&self, id: Id) -> usize {
+        self.set.set_size_of(id)
     }
 
     /// Split a value from it's set, creating it's own unique set.
     ///
     /// Inserts the value if not present.
     ///
+    /// This takes time linear in the size of the value's set, as every
+    /// other member is pointed straight at the root. Splitting all `k`
+    /// values out of one set one at a time therefore takes `O(k²)`.
+    ///
     /// # Examples
     /// ```
     /// # use disjoint_hash_set::DisjointHashSet;
@@ -273,13 +564,43 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
     /// assert!(!set.connected(&"this", &"that"));
     /// ```
     pub fn split(&mut self, value: T) {
-        let id = self.split_inner(value);
-        self.get_mut(id).parent = id;
+        self.split_inner(value);
+    }
+
+    /// Split a value that is already present from it's set.
+    ///
+    /// Unlike `split` the value may be a borrowed form of the value type.
+    /// Returns `false`, doing nothing, if the value is not present.
+    ///
+    /// Takes linear time, like [`split`](Self::split).
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::new();
+    /// set.insert_set(vec!["this".to_string(), "that".to_string()]);
+    /// assert!(set.try_split("this"));
+    /// assert!(!set.connected("this", "that"));
+    /// assert!(!set.try_split("other"));
+    /// ```
+    pub fn try_split<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        match self.values.get_index_of(value) {
+            Some(index) => {
+                self.set.detach(Id(index));
+                true
+            }
+            None => false,
+        }
     }
 
     /// Split a value into the set of another.
     ///
-    /// Inserts the value if not present.
+    /// Inserts the value if not present. Takes linear time, like
+    /// [`split`](Self::split).
     ///
     /// # Examples
     /// ```
@@ -299,7 +620,8 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
 
     /// Split a value into the set specified by it's id.
     ///
-    /// Inserts the value if not present.
+    /// Inserts the value if not present. Takes linear time, like
+    /// [`split`](Self::split).
     ///
     /// # Examples
     /// ```
@@ -317,25 +639,445 @@ impl<T: Hash + Eq> DisjointHashSet<T> {
         self.union_inner(id, into);
     }
 
+    /// Remove a value from the disjoint set, returning it if it was present.
+    ///
+    /// The rest of the value's set stays connected. The value at the last
+    /// index moves into the slot of the removed value, so this invalidates any
+    /// `Id`s held for either.
+    ///
+    /// The value is split from its set first, so this takes linear time like
+    /// [`split`](Self::split).
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::new();
+    /// set.insert_set(vec!["this", "that", "other"]);
+    /// assert_eq!(set.remove(&"that"), Some("that"));
+    /// assert_eq!(set.remove(&"that"), None);
+    /// assert!(!set.contains(&"that"));
+    /// assert!(set.connected(&"this", &"other"));
+    /// assert_eq!(set.size(), 2);
+    /// ```
+    pub fn remove<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (index, value, ()) = self.values.swap_remove_full(value)?;
+        self.set.swap_remove(Id(index));
+        Some(value)
+    }
+
+    /// Returns an iterator over the values in the same set as a value.
+    ///
+    /// The iterator is empty if the value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that", "other"]);
+    /// set.union("this", "that");
+    /// let mut members: Vec<_> = set.members(&"this").collect();
+    /// members.sort();
+    /// assert_eq!(members, vec![&"that", &"this"]);
+    /// ```
+    pub fn members<Q>(&self, value: &Q) -> Members<'_, T, S, I>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        Members {
+            values: &self.values,
+            ring: self
+                .values
+                .get_index_of(value)
+                .map(|index| self.set.ring(Id(index))),
+        }
+    }
+
+    /// Returns an iterator over the values in the set specified by it's id.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::new();
+    /// set.insert_set(vec!["this", "that"]);
+    /// let id = set.find_or_insert("other");
+    /// assert_eq!(set.members_of(id).collect::<Vec<_>>(), vec![&"other"]);
+    /// ```
+    pub fn members_of(&self, id: Id) -> Members<'_, T, S, I> {
+        Members {
+            values: &self.values,
+            ring: Some(self.set.ring(id)),
+        }
+    }
+
+    /// Returns an iterator over the disjoint sets, each collected into a `Vec`
+    /// of references to its values.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that", "other"]);
+    /// set.union("this", "that");
+    /// let mut sets: Vec<_> = set.sets().collect();
+    /// sets.iter_mut().for_each(|s| s.sort());
+    /// sets.sort();
+    /// assert_eq!(sets, vec![vec![&"other"], vec![&"that", &"this"]]);
+    /// ```
+    pub fn sets(&self) -> impl Iterator<Item = Vec<&T>> {
+        (0..self.size())
+            .map(Id)
+            .filter(move |&id| self.set.get(id).parent == id)
+            .map(move |id| self.members_of(id).collect())
+    }
+
+    /// Consumes the disjoint set, returning each set as a `Vec` of its values.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<&str>::with_values(vec!["this", "that", "other"]);
+    /// set.union("this", "that");
+    /// let mut sets = set.into_sets();
+    /// sets.iter_mut().for_each(|s| s.sort());
+    /// sets.sort();
+    /// assert_eq!(sets, vec![vec!["other"], vec!["that", "this"]]);
+    /// ```
+    pub fn into_sets(self) -> Vec<Vec<T>> {
+        let roots: Vec<Id> = (0..self.size()).map(|i| self.set.root(Id(i))).collect();
+        let mut sets: Vec<Vec<T>> = roots.iter().map(|_| Vec::new()).collect();
+        for (i, (value, ())) in self.values.into_iter().enumerate() {
+            sets[roots[i].0].push(value);
+        }
+        sets.into_iter().filter(|set| !set.is_empty()).collect()
+    }
+
     fn split_inner(&mut self, value: T) -> Id {
         let id = self.insert_inner(value);
-        let value = self.get(id);
+        self.set.detach(id);
+        id
+    }
+}
+
+/// An iterator over the values in a single set of a `DisjointHashSet`.
+///
+/// Created by [`DisjointHashSet::members`] and [`DisjointHashSet::members_of`].
+pub struct Members<'a, T, S = RandomState, I = usize> {
+    values: &'a IndexMap<T, (), S>,
+    ring: Option<Ring<'a, I>>,
+}
+
+impl<'a, T, S, I: IndexType> Iterator for Members<'a, T, S, I> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.ring.as_mut()?.next()?;
+        self.values.get_index(id.0).map(|(value, ())| value)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Hash + Eq + Send + Sync> DisjointHashSet<T> {
+    /// Create a `DisjointHashSet` from a parallel iterator of edges, unioning
+    /// the two values of each edge.
+    ///
+    /// Every value is hashed once, and the values are split into shards by
+    /// hash and given dense ids shard by shard, all in parallel. The edges
+    /// are then unioned from many threads at once over an array of atomic
+    /// parents, and the values are put in the map in one pass without hashing
+    /// them again. Values are numbered in the order they first appear in the
+    /// edges, as with `union` in a loop.
+    ///
+    /// Only the numbering and the final map are built on one thread, which
+    /// is about a third of the work. On a single thread the `from_edges`
+    /// benchmark takes about as long as `union` in a loop for a million
+    /// random edges, and 1.5 times as long for a hundred thousand; with more
+    /// threads the rest of the work is split between them.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// use rayon::prelude::*;
+    ///
+    /// let edges: Vec<(u32, u32)> = (0..1000).map(|i| (i, i % 10)).collect();
+    /// let mut set = DisjointHashSet::from_edges_par(edges);
+    /// assert_eq!(set.num_sets(), 10);
+    /// assert!(set.connected(&999, &9));
+    /// ```
+    pub fn from_edges_par<E>(edges: E) -> Self
+    where
+        E: rayon::iter::IntoParallelIterator<Item = (T, T)>,
+    {
+        use crate::concurrent_disjoint_hash_set::{root, union};
+        use indexmap::map::raw_entry_v1::{RawEntryApiV1, RawEntryMut};
+        use rayon::prelude::*;
+        use std::sync::atomic::AtomicUsize;
+
+        // endpoint `k` is one side of edge `k / 2`
+        let edges: Vec<(T, T)> = edges.into_par_iter().collect();
+        let endpoint = |k: usize| {
+            let (value, other) = &edges[k / 2];
+            if k.is_multiple_of(2) {
+                value
+            } else {
+                other
+            }
+        };
 
-        if value.size == 1 {
-            return id;
+        // deal the endpoints into shards by hash, keeping them in order, so
+        // equal values share a shard and every shard is deduplicated alone.
+        // the shard takes bits the hash table does not use for probing
+        const SHARDS: usize = 256;
+        let shard_of = |hash: u64| (hash >> 40) as usize % SHARDS;
+        let hash_builder = RandomState::new();
+        let hashes: Vec<u64> = (0..2 * edges.len())
+            .into_par_iter()
+            .map(|k| hash_builder.hash_one(endpoint(k)))
+            .collect();
+        let mut starts = vec![0; SHARDS + 1];
+        for &hash in &hashes {
+            starts[shard_of(hash) + 1] += 1;
+        }
+        for shard in 0..SHARDS {
+            starts[shard + 1] += starts[shard];
+        }
+        let mut ends = starts.clone();
+        let mut shards = vec![0; hashes.len()];
+        for (k, &hash) in hashes.iter().enumerate() {
+            let end = &mut ends[shard_of(hash)];
+            shards[*end] = k;
+            *end += 1;
+        }
+
+        // the first endpoint with each value, found with a map of endpoints
+        // per shard. the map keeps their hashes, so it never hashes a key
+        let firsts: Vec<usize> = starts
+            .par_windows(2)
+            .flat_map_iter(|range| {
+                let (shard, hashes) = (&shards[range[0]..range[1]], &hashes);
+                let mut seen = IndexMap::<usize, ()>::with_capacity(shard.len());
+                shard.iter().map(move |&k| {
+                    let hash = hashes[k];
+                    match seen
+                        .raw_entry_mut_v1()
+                        .from_hash(hash, |&first| endpoint(first) == endpoint(k))
+                    {
+                        RawEntryMut::Occupied(entry) => *entry.key(),
+                        RawEntryMut::Vacant(entry) => {
+                            entry.insert_hashed_nocheck(hash, k, ());
+                            k
+                        }
+                    }
+                })
+            })
+            .collect();
+        let mut first_of = vec![0; hashes.len()];
+        for (&k, &first) in shards.iter().zip(&firsts) {
+            first_of[k] = first;
         }
-        let mut data_iter = self.data.iter_mut();
-        let new_parent = {
-            if id == value.parent {
-                Id(data_iter.position(|v| v.parent == id).unwrap())
+        let mut ids = vec![0; first_of.len()];
+        let mut len = 0;
+        for (k, &first) in first_of.iter().enumerate() {
+            ids[k] = if first == k {
+                len += 1;
+                len - 1
             } else {
-                value.parent
+                ids[first]
+            };
+        }
+
+        let parents: Vec<AtomicUsize> = (0..len).map(AtomicUsize::new).collect();
+        let parent = |id: usize| &parents[id];
+        ids.par_chunks(2).for_each(|edge| {
+            union(parent, edge[0], edge[1]);
+        });
+        let roots: Vec<usize> = (0..len)
+            .into_par_iter()
+            .map(|id| root(parent, id))
+            .collect();
+
+        let mut values = IndexMap::with_capacity_and_hasher(len, hash_builder);
+        let endpoints = edges.into_iter().flat_map(|(value, other)| [value, other]);
+        for (k, value) in endpoints.enumerate() {
+            if first_of[k] != k {
+                continue;
+            }
+            let hash = hashes[k];
+            match values.raw_entry_mut_v1().from_hash(hash, |_| false) {
+                RawEntryMut::Vacant(entry) => {
+                    entry.insert_hashed_nocheck(hash, value, ());
+                }
+                RawEntryMut::Occupied(_) => unreachable!("values were deduplicated"),
             }
+        }
+        Self::from_roots(values, &roots)
+    }
+}
+
+impl<T: Hash + Eq, S, I: IndexType> DisjointHashSet<T, S, I> {
+    /// The sets in the order of their lowest index, each in index order, so
+    /// the output only depends on the operations performed.
+    fn ordered_sets(&self) -> Vec<Vec<&T>> {
+        let values = |set: Vec<usize>| {
+            let values = set.into_iter().filter_map(|i| self.values.get_index(i));
+            values.map(|(value, ())| value).collect()
         };
-        for mut v in data_iter.filter(|v| v.parent == id) {
-            v.parent = new_parent;
+        self.set.ordered_sets().into_iter().map(values).collect()
+    }
+}
+
+/// Formats the partition as a set of sets, like `{{"this", "that"}, {"other"}}`.
+///
+/// Values are listed in index order and sets in the order of their lowest
+/// index. That is insertion order until a value is removed, which moves the
+/// last value into its index.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashSet;
+/// let mut set = DisjointHashSet::new();
+/// set.union("this", "that");
+/// set.insert("other");
+/// assert_eq!(format!("{:?}", set), r#"{{"this", "that"}, {"other"}}"#);
+/// ```
+impl<T: Hash + Eq + fmt::Debug, S, I: IndexType> fmt::Debug for DisjointHashSet<T, S, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.ordered_sets().iter().map(|set| DebugSet(set)))
+            .finish()
+    }
+}
+
+/// Formats the partition as a set of sets, like `{{a, b}, {c}}`.
+///
+/// Values are sorted within each set and sets are sorted, so the output only
+/// depends on the partition.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashSet;
+/// let mut set = DisjointHashSet::new();
+/// set.union(3, 1);
+/// set.insert(2);
+/// assert_eq!(set.to_string(), "{{1, 3}, {2}}");
+/// ```
+impl<T, S, I> fmt::Display for DisjointHashSet<T, S, I>
+where
+    T: Hash + Eq + fmt::Display + Ord,
+    I: IndexType,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sets = self.ordered_sets();
+        sets.iter_mut().for_each(|set| set.sort());
+        sets.sort();
+        f.write_str("{")?;
+        for (i, set) in sets.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str("{")?;
+            for (j, value) in set.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(", ")?;
+                }
+                fmt::Display::fmt(value, f)?;
+            }
+            f.write_str("}")?;
+        }
+        f.write_str("}")
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default, I: IndexType> Default for DisjointHashSet<T, S, I> {
+    fn default() -> Self {
+        Self {
+            values: IndexMap::with_hasher(S::default()),
+            set: DisjointSet::new(),
+        }
+    }
+}
+
+/// Two disjoint sets are equal if they hold the same values partitioned into
+/// the same sets, however those sets were built.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashSet;
+/// let mut set = DisjointHashSet::new();
+/// set.union("this", "that");
+/// set.insert("other");
+/// let mut other = DisjointHashSet::new();
+/// other.insert("other");
+/// other.union("that", "this");
+/// assert_eq!(set, other);
+/// other.union("other", "this");
+/// assert_ne!(set, other);
+/// ```
+impl<T: Hash + Eq, S: BuildHasher, I: IndexType> PartialEq for DisjointHashSet<T, S, I> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.size() != other.size() || self.num_sets() != other.num_sets() {
+            return false;
+        }
+        // every set must be contained in a set of the same size in `other`
+        self.sets().all(|set| {
+            let root = match other.find_ref(set[0]) {
+                Some(root) => root,
+                None => return false,
+            };
+            other.set_size_of(root) == set.len()
+                && set.iter().all(|value| other.find_ref(*value) == Some(root))
+        })
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher, I: IndexType> Eq for DisjointHashSet<T, S, I> {}
+
+/// Collects values into a disjoint set, each in its own set.
+impl<T: Hash + Eq, S: BuildHasher + Default, I: IndexType> FromIterator<T>
+    for DisjointHashSet<T, S, I>
+{
+    fn from_iter<V: IntoIterator<Item = T>>(iter: V) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Collects edges into a disjoint set, unioning the two values of each edge.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashSet;
+/// let mut set: DisjointHashSet<u32> = vec![(1, 2), (2, 3), (4, 5)].into_iter().collect();
+/// assert!(set.connected(&1, &3));
+/// assert_eq!(set.num_sets(), 2);
+/// ```
+impl<T: Hash + Eq, S: BuildHasher + Default, I: IndexType> FromIterator<(T, T)>
+    for DisjointHashSet<T, S, I>
+{
+    fn from_iter<E: IntoIterator<Item = (T, T)>>(iter: E) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+/// Inserts values, each in its own set unless already present.
+impl<T: Hash + Eq, S: BuildHasher, I: IndexType> Extend<T> for DisjointHashSet<T, S, I> {
+    fn extend<V: IntoIterator<Item = T>>(&mut self, iter: V) {
+        for value in iter {
+            self.insert_inner(value);
+        }
+    }
+}
+
+/// Unions the two values of each edge, inserting them if not present.
+impl<T: Hash + Eq, S: BuildHasher, I: IndexType> Extend<(T, T)> for DisjointHashSet<T, S, I> {
+    fn extend<E: IntoIterator<Item = (T, T)>>(&mut self, iter: E) {
+        for (value, other) in iter {
+            self.union(value, other);
         }
-        self.get_mut(id).size = 1;
-        id
     }
 }
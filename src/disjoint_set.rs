@@ -0,0 +1,834 @@
+use std::fmt;
+use std::hash::Hash;
+
+/// Identifies a set in a `DisjointHashSet`.
+///
+/// The `Id` returned by [`DisjointHashSet::find`] is the id of the set's root,
+/// which may change whenever sets are unioned or split, or values are
+/// removed. An `Id` is therefore only valid until the next such change; after
+/// that `find` the value again to get the current `Id` of its set.
+///
+/// [`DisjointHashSet::find`]: crate::DisjointHashSet::find
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Id(pub(crate) usize);
+
+/// How a disjoint set decides which root is kept when two sets are unioned.
+///
+/// Either way the smaller tree is attached under the larger one, keeping
+/// paths short; the strategies differ in how "smaller" is measured.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum UnionBy {
+    /// Attach the set with fewer values under the set with more.
+    #[default]
+    Size,
+    /// Attach the tree of lower rank, an upper bound on its height, under the
+    /// tree of higher rank.
+    Rank,
+}
+
+/// What happened when two sets were unioned.
+///
+/// Roots are `Id`s, except for a [`DisjointSet`] where they are indices.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Union<K = Id> {
+    /// The values were already in the same set, which has root `root`.
+    AlreadyConnected { root: K },
+    /// Two sets were merged into one of `size` values with root `root`. The
+    /// root of the other set, `absorbed`, no longer identifies a set.
+    Merged { root: K, absorbed: K, size: usize },
+}
+
+impl<K: Copy> Union<K> {
+    /// Returns `true` if two sets were merged.
+    pub fn is_merged(&self) -> bool {
+        matches!(self, Union::Merged { .. })
+    }
+
+    /// Returns the root of the resulting set.
+    pub fn root(&self) -> K {
+        match *self {
+            Union::AlreadyConnected { root } | Union::Merged { root, .. } => root,
+        }
+    }
+
+    fn map<L>(self, f: impl Fn(K) -> L) -> Union<L> {
+        match self {
+            Union::AlreadyConnected { root } => Union::AlreadyConnected { root: f(root) },
+            Union::Merged {
+                root,
+                absorbed,
+                size,
+            } => Union::Merged {
+                root: f(root),
+                absorbed: f(absorbed),
+                size,
+            },
+        }
+    }
+}
+
+/// An unsigned integer type used for the indices of a [`DisjointSet`].
+///
+/// Each index takes two integers of this type, its parent and the next
+/// member of its set, plus a rank byte when unioning by rank. A root keeps
+/// its set's size in place of its parent, marked by the type's top bit, so
+/// a set holds up to half the type's maximum indices: `i32::MAX` with `u32`,
+/// at 8 bytes an index rather than 16 with `usize`.
+pub trait IndexType: Copy + Eq + Ord + Hash + fmt::Debug {
+    /// The largest value of the type.
+    const MAX: Self;
+
+    /// Converts a `usize`, returning `None` if it doesn't fit.
+    fn try_from_usize(index: usize) -> Option<Self>;
+
+    /// Converts the index to a `usize`.
+    fn index(self) -> usize;
+}
+
+macro_rules! index_type {
+    ($($t:ty),*) => {
+        $(
+            impl IndexType for $t {
+                const MAX: Self = <$t>::MAX;
+
+                fn try_from_usize(index: usize) -> Option<Self> {
+                    Self::try_from(index).ok()
+                }
+
+                fn index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+index_type!(u8, u16, u32, u64, usize);
+
+fn narrow<I: IndexType>(index: usize) -> I {
+    I::try_from_usize(index).expect("index out of range for the index type")
+}
+
+/// Links from this mark up belong to roots, holding the mark plus the size
+/// of the set.
+fn root_mark<I: IndexType>() -> usize {
+    I::MAX.index() / 2 + 1
+}
+
+/// A node with its indices widened to `usize`, as written to snapshots.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct Node {
+    pub(crate) size: usize,
+    pub(crate) rank: u8,
+    pub(crate) parent: Id,
+    /// next member of the same set, forming a circular list
+    pub(crate) next: Id,
+}
+
+/// A disjoint set over the dense indices `0..size`, without any hashing.
+///
+/// This is the union-find forest a `DisjointHashSet` keeps for its values,
+/// for when the values already are indices. Methods taking an index panic if
+/// it is out of bounds.
+///
+/// The indices are of type `I`, which also sets how much memory each takes;
+/// see [`IndexType`].
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointSet;
+/// let mut set = DisjointSet::<u32>::with_size(4);
+/// set.union(0, 1);
+/// set.union(2, 3);
+/// assert!(set.connected(1, 0));
+/// assert!(!set.connected(1, 2));
+/// assert_eq!(set.num_sets(), 2);
+/// ```
+#[derive(Clone)]
+pub struct DisjointSet<I = usize> {
+    /// the parent of each index, or for a root its set's size above the mark
+    links: Vec<I>,
+    /// next member of the same set, forming a circular list
+    next: Vec<I>,
+    /// the rank of each index, only kept when unioning by rank
+    ranks: Vec<u8>,
+    num_sets: usize,
+    pub(crate) union_by: UnionBy,
+}
+
+impl<I: IndexType> DisjointSet<I> {
+    /// Create an empty `DisjointSet`.
+    pub fn new() -> Self {
+        Self {
+            links: Vec::new(),
+            next: Vec::new(),
+            ranks: Vec::new(),
+            num_sets: 0,
+            union_by: UnionBy::default(),
+        }
+    }
+
+    /// Create an empty `DisjointSet` with specified capacity.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            links: Vec::with_capacity(cap),
+            next: Vec::with_capacity(cap),
+            ..Self::new()
+        }
+    }
+
+    /// Create a `DisjointSet` of the indices `0..size`, each in its own set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is more than `I` has room for; see [`IndexType`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let set = DisjointSet::<u32>::with_size(3);
+    /// assert_eq!(set.size(), 3);
+    /// assert_eq!(set.num_sets(), 3);
+    /// ```
+    pub fn with_size(size: usize) -> Self {
+        let mut set = Self::with_capacity(size);
+        for _ in 0..size {
+            set.push_node();
+        }
+        set
+    }
+
+    /// Create an empty `DisjointSet` using the specified union strategy.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointSet, UnionBy};
+    /// let mut set = DisjointSet::<u32>::with_union_by(UnionBy::Rank);
+    /// let (a, b) = (set.push(), set.push());
+    /// set.union(a, b);
+    /// assert!(set.connected(a, b));
+    /// ```
+    pub fn with_union_by(union_by: UnionBy) -> Self {
+        Self {
+            union_by,
+            ..Self::new()
+        }
+    }
+
+    /// Build a disjoint set from the root of each index.
+    pub(crate) fn from_roots(roots: &[usize]) -> Self {
+        let mut set = Self::with_size(roots.len());
+        for (id, &root) in roots.iter().enumerate() {
+            if id == root {
+                continue;
+            }
+            let (id, root) = (Id(id), Id(root));
+            // link each member into the list right after its root
+            set.set_parent(id, root);
+            set.set_next(id, set.next(root));
+            set.set_next(root, id);
+            set.set_root_size(root, set.root_size(root) + 1);
+            set.set_rank(root, 1);
+            set.num_sets -= 1;
+        }
+        set
+    }
+
+    /// Build a disjoint set from its nodes, as long as they form a valid
+    /// forest whose indices fit `I`.
+    ///
+    /// Every parent chain has to end at a root, every root's member list has
+    /// to hold exactly the members of its tree, every root's size has to
+    /// match, and no rank may be above `usize::BITS`. Sizes of other nodes aren't used, so they're reset.
+    pub(crate) fn from_nodes(nodes: Vec<Node>, num_sets: usize, union_by: UnionBy) -> Option<Self> {
+        let len = nodes.len();
+        if len > Self::max_size() {
+            return None;
+        }
+        // a rank is at most the log of the set's size, so anything above the
+        // bits of a usize would overflow when unioned
+        if nodes.iter().any(|node| {
+            node.parent.0 >= len || node.next.0 >= len || u32::from(node.rank) > usize::BITS
+        }) {
+            return None;
+        }
+
+        // find each node's root, caching along the way so the whole pass is
+        // linear. a chain longer than `len` must be a cycle
+        let mut roots = vec![usize::MAX; len];
+        let mut path = Vec::new();
+        for i in 0..len {
+            let mut id = i;
+            while roots[id] == usize::MAX && nodes[id].parent.0 != id {
+                path.push(id);
+                if path.len() > len {
+                    return None;
+                }
+                id = nodes[id].parent.0;
+            }
+            let root = if roots[id] == usize::MAX {
+                id
+            } else {
+                roots[id]
+            };
+            roots[id] = root;
+            for id in path.drain(..) {
+                roots[id] = root;
+            }
+        }
+
+        // walk each root's member list once, which must visit exactly the
+        // nodes of its tree
+        let mut visited = vec![false; len];
+        let mut num_roots = 0;
+        for root in (0..len).filter(|&i| roots[i] == i) {
+            num_roots += 1;
+            let mut size = 0;
+            let mut id = root;
+            loop {
+                if visited[id] || roots[id] != root {
+                    return None;
+                }
+                visited[id] = true;
+                size += 1;
+                id = nodes[id].next.0;
+                if id == root {
+                    break;
+                }
+            }
+            if nodes[root].size != size {
+                return None;
+            }
+        }
+        if num_roots != num_sets || visited.contains(&false) {
+            return None;
+        }
+
+        let mut set = Self::with_capacity(len);
+        set.union_by = union_by;
+        set.num_sets = num_sets;
+        for (i, node) in nodes.iter().enumerate() {
+            set.links.push(if roots[i] == i {
+                narrow(root_mark::<I>() + node.size)
+            } else {
+                narrow(node.parent.0)
+            });
+            set.next.push(narrow(node.next.0));
+            if union_by == UnionBy::Rank {
+                set.ranks.push(node.rank);
+            }
+        }
+        Some(set)
+    }
+
+    /// Returns the number of indices in the disjoint set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::new();
+    /// assert_eq!(set.size(), 0);
+    /// set.push();
+    /// assert_eq!(set.size(), 1);
+    /// ```
+    pub fn size(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Returns the number of disjoint sets.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(3);
+    /// assert_eq!(set.num_sets(), 3);
+    /// set.union(0, 1);
+    /// assert_eq!(set.num_sets(), 2);
+    /// ```
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /// Add the next index in its own set, returning it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `I` has no room for another index; see [`IndexType`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(2);
+    /// assert_eq!(set.push(), 2);
+    /// assert_eq!(set.num_sets(), 3);
+    /// ```
+    pub fn push(&mut self) -> I {
+        narrow(self.push_node().0)
+    }
+
+    pub(crate) fn push_node(&mut self) -> Id {
+        self.assert_room();
+        let id = Id(self.size());
+        self.links.push(narrow(root_mark::<I>() + 1));
+        self.next.push(narrow(id.0));
+        if self.union_by == UnionBy::Rank {
+            self.ranks.push(0);
+        }
+        self.num_sets += 1;
+        id
+    }
+
+    /// The most indices a set can hold, so that a root can still hold the
+    /// size of a set of all of them.
+    pub(crate) fn max_size() -> usize {
+        I::MAX.index() - root_mark::<I>()
+    }
+
+    /// Panics unless there is room for one more index.
+    pub(crate) fn assert_room(&self) {
+        assert!(
+            self.size() < Self::max_size(),
+            "too many values for the index type"
+        );
+    }
+
+    /// Find the root index of the set an index is in.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(3);
+    /// set.union(0, 1);
+    /// assert_eq!(set.find(0), set.find(1));
+    /// assert_eq!(set.find(2), 2);
+    /// ```
+    pub fn find(&mut self, index: I) -> I {
+        narrow(self.compress_path(Id(index.index())).0)
+    }
+
+    /// Find the root index of the set an index is in without compressing the
+    /// path to it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(2);
+    /// set.union(0, 1);
+    /// let root = set.find(0);
+    /// let set = &set;
+    /// assert_eq!(set.find_ref(1), root);
+    /// ```
+    pub fn find_ref(&self, index: I) -> I {
+        narrow(self.root(Id(index.index())).0)
+    }
+
+    /// Returns `true` if the two indices are in the same set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(2);
+    /// assert!(!set.connected(0, 1));
+    /// set.union(0, 1);
+    /// assert!(set.connected(0, 1));
+    /// ```
+    pub fn connected(&mut self, index: I, other: I) -> bool {
+        self.find(index) == self.find(other)
+    }
+
+    /// Returns `true` if the two indices are in the same set, without
+    /// compressing the paths to their roots.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(2);
+    /// set.union(0, 1);
+    /// let set = &set;
+    /// assert!(set.connected_ref(0, 1));
+    /// ```
+    pub fn connected_ref(&self, index: I, other: I) -> bool {
+        self.find_ref(index) == self.find_ref(other)
+    }
+
+    /// Unions the sets of two indices.
+    ///
+    /// Returns whether the sets were merged or were already the same set,
+    /// with the root indices involved.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointSet, Union};
+    /// let mut set = DisjointSet::<u32>::with_size(2);
+    /// assert!(set.union(0, 1).is_merged());
+    /// let root = set.find(0);
+    /// assert_eq!(set.union(1, 0), Union::AlreadyConnected { root });
+    /// ```
+    pub fn union(&mut self, index: I, other: I) -> Union<I> {
+        let index = self.compress_path(Id(index.index()));
+        let other = self.compress_path(Id(other.index()));
+        self.union_inner(index, other).map(|id| narrow(id.0))
+    }
+
+    /// Returns the number of indices in the same set as an index.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(3);
+    /// set.union(0, 1);
+    /// assert_eq!(set.set_size(0), 2);
+    /// assert_eq!(set.set_size(2), 1);
+    /// ```
+    pub fn set_size(&self, index: I) -> usize {
+        self.set_size_of(Id(index.index()))
+    }
+
+    /// Split an index from its set, creating its own unique set.
+    ///
+    /// Takes linear time, like
+    /// [`DisjointHashSet::split`](crate::DisjointHashSet::split).
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(3);
+    /// set.union(0, 1);
+    /// set.union(1, 2);
+    /// set.split(1);
+    /// assert!(set.connected(0, 2));
+    /// assert!(!set.connected(0, 1));
+    /// ```
+    pub fn split(&mut self, index: I) {
+        self.detach(Id(index.index()));
+    }
+
+    /// Split an index into the set of another.
+    ///
+    /// Takes linear time, like [`split`](Self::split).
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(3);
+    /// set.union(0, 1);
+    /// set.split_into(0, 2);
+    /// assert!(!set.connected(0, 1));
+    /// assert!(set.connected(0, 2));
+    /// ```
+    pub fn split_into(&mut self, index: I, into: I) {
+        let id = Id(index.index());
+        self.detach(id);
+        let into = self.compress_path(Id(into.index()));
+        self.union_inner(id, into);
+    }
+
+    /// Returns an iterator over the indices in the same set as an index.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(3);
+    /// set.union(0, 2);
+    /// let mut members: Vec<_> = set.members(0).collect();
+    /// members.sort();
+    /// assert_eq!(members, vec![0, 2]);
+    /// ```
+    pub fn members(&self, index: I) -> impl Iterator<Item = I> + '_ {
+        self.ring(Id(index.index())).map(|id| narrow(id.0))
+    }
+
+    /// Returns an iterator over the disjoint sets, each collected into a `Vec`
+    /// of its indices.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointSet;
+    /// let mut set = DisjointSet::<u32>::with_size(3);
+    /// set.union(0, 2);
+    /// let mut sets: Vec<_> = set.sets().collect();
+    /// sets.iter_mut().for_each(|s| s.sort());
+    /// sets.sort();
+    /// assert_eq!(sets, vec![vec![0, 2], vec![1]]);
+    /// ```
+    pub fn sets(&self) -> impl Iterator<Item = Vec<I>> + '_ {
+        (0..self.size())
+            .map(Id)
+            .filter(move |&id| self.is_root(id))
+            .map(move |id| self.ring(id).map(|id| narrow(id.0)).collect())
+    }
+
+    /// The sets in the order of their lowest index, each in index order.
+    pub(crate) fn ordered_sets(&self) -> Vec<Vec<usize>> {
+        let mut index_of_root = vec![usize::MAX; self.size()];
+        let mut sets: Vec<Vec<usize>> = Vec::new();
+        for i in 0..self.size() {
+            let root = self.root(Id(i)).0;
+            if index_of_root[root] == usize::MAX {
+                index_of_root[root] = sets.len();
+                sets.push(Vec::new());
+            }
+            sets[index_of_root[root]].push(i);
+        }
+        sets
+    }
+
+    /// The node of an index, widened.
+    pub(crate) fn get(&self, id: Id) -> Node {
+        let root = self.is_root(id);
+        Node {
+            size: if root { self.root_size(id) } else { 1 },
+            rank: self.rank(id),
+            parent: if root { id } else { self.parent(id) },
+            next: self.next(id),
+        }
+    }
+
+    pub(crate) fn is_root(&self, id: Id) -> bool {
+        self.links[id.0].index() >= root_mark::<I>()
+    }
+
+    /// The parent of a node, which is itself for a root.
+    fn parent(&self, id: Id) -> Id {
+        let link = self.links[id.0].index();
+        if link >= root_mark::<I>() {
+            id
+        } else {
+            Id(link)
+        }
+    }
+
+    fn set_parent(&mut self, id: Id, parent: Id) {
+        self.links[id.0] = narrow(parent.0);
+    }
+
+    /// The size of a root's set.
+    fn root_size(&self, root: Id) -> usize {
+        self.links[root.0].index() - root_mark::<I>()
+    }
+
+    /// Make a node a root with a set of `size`.
+    fn set_root_size(&mut self, root: Id, size: usize) {
+        self.links[root.0] = narrow(root_mark::<I>() + size);
+    }
+
+    fn next(&self, id: Id) -> Id {
+        Id(self.next[id.0].index())
+    }
+
+    fn set_next(&mut self, id: Id, next: Id) {
+        self.next[id.0] = narrow(next.0);
+    }
+
+    /// The rank of a node, always 0 unless unioning by rank.
+    fn rank(&self, id: Id) -> u8 {
+        self.ranks.get(id.0).copied().unwrap_or(0)
+    }
+
+    fn set_rank(&mut self, id: Id, rank: u8) {
+        if let Some(slot) = self.ranks.get_mut(id.0) {
+            *slot = rank;
+        }
+    }
+
+    /// Find the root of a set without compressing the path to it.
+    pub(crate) fn root(&self, mut id: Id) -> Id {
+        let mut parent = self.parent(id);
+        while parent != id {
+            id = parent;
+            parent = self.parent(id);
+        }
+        id
+    }
+
+    pub(crate) fn compress_path(&mut self, mut id: Id) -> Id {
+        // path halving
+        let mut parent = self.parent(id);
+        while parent != id {
+            let grandparent = self.parent(parent);
+            self.set_parent(id, grandparent);
+            id = parent;
+            parent = self.parent(id);
+        }
+        id
+    }
+
+    /// value and other are assumed to be the root
+    pub(crate) fn union_inner(&mut self, value: Id, other: Id) -> Union {
+        if value == other {
+            return Union::AlreadyConnected { root: value };
+        }
+
+        // attach the smaller tree under the larger one
+        let value_larger = match self.union_by {
+            UnionBy::Size => self.root_size(value) > self.root_size(other),
+            UnionBy::Rank => self.rank(value) > self.rank(other),
+        };
+        let (root, child) = if value_larger {
+            (value, other)
+        } else {
+            (other, value)
+        };
+        let size = self.root_size(root) + self.root_size(child);
+        self.set_parent(child, root);
+        self.set_root_size(root, size);
+        self.set_rank(root, self.rank(root).max(self.rank(child) + 1));
+
+        self.num_sets -= 1;
+
+        // splice the two circular member lists together
+        let (value_next, other_next) = (self.next(value), self.next(other));
+        self.set_next(value, other_next);
+        self.set_next(other, value_next);
+        Union::Merged {
+            root,
+            absorbed: child,
+            size,
+        }
+    }
+
+    /// Undo the `union_inner` that linked `child` under `root`, given the size
+    /// of `child`'s set and the rank of `root` before. Only valid while no
+    /// other change has been made since.
+    pub(crate) fn unlink(&mut self, root: Id, child: Id, child_size: usize, rank: u8) {
+        // swapping the successors again splits the member lists apart
+        let (root_next, child_next) = (self.next(root), self.next(child));
+        self.set_next(root, child_next);
+        self.set_next(child, root_next);
+        self.set_root_size(root, self.root_size(root) - child_size);
+        self.set_root_size(child, child_size);
+        self.set_rank(root, rank);
+        self.num_sets += 1;
+    }
+
+    pub(crate) fn set_size_of(&self, id: Id) -> usize {
+        self.root_size(self.root(id))
+    }
+
+    /// Detach a node from its set, leaving it as a singleton.
+    pub(crate) fn detach(&mut self, id: Id) {
+        let root = self.compress_path(id);
+        let next = self.next(id);
+        if next == id {
+            return;
+        }
+        self.num_sets += 1;
+
+        // any member may be a descendant of the detached node, so point every
+        // remaining member straight at the root while unlinking the node from
+        // the member list. a detached root hands the set over to its next member
+        let size = self.root_size(root) - 1;
+        let root = if root == id { next } else { root };
+        let mut member = next;
+        loop {
+            if member != root {
+                self.set_parent(member, root);
+            }
+            let following = self.next(member);
+            if following == id {
+                self.set_next(member, next);
+                break;
+            }
+            member = following;
+        }
+        self.set_root_size(root, size);
+        self.set_rank(root, if size > 1 { 1 } else { 0 });
+        self.set_root_size(id, 1);
+        self.set_next(id, id);
+        self.set_rank(id, 0);
+    }
+
+    /// Remove a node, moving the last node into its slot like
+    /// `Vec::swap_remove`.
+    pub(crate) fn swap_remove(&mut self, id: Id) {
+        self.detach(id);
+        self.num_sets -= 1;
+        self.links.swap_remove(id.0);
+        self.next.swap_remove(id.0);
+        if self.union_by == UnionBy::Rank {
+            self.ranks.swap_remove(id.0);
+        }
+
+        // the last node moved into the freed slot, so repoint the members
+        // of its set that referred to it
+        let last = Id(self.size());
+        if id != last {
+            let mut member = id;
+            loop {
+                if self.parent(member) == last {
+                    self.set_parent(member, id);
+                }
+                if self.next(member) == last {
+                    self.set_next(member, id);
+                }
+                member = self.next(member);
+                if member == id {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The members of the set of a node, starting from the node.
+    pub(crate) fn ring(&self, id: Id) -> Ring<'_, I> {
+        Ring {
+            set: self,
+            start: id,
+            next: Some(id),
+        }
+    }
+}
+
+/// Follows the circular member list of a set once around.
+#[derive(Clone)]
+pub(crate) struct Ring<'a, I> {
+    set: &'a DisjointSet<I>,
+    start: Id,
+    next: Option<Id>,
+}
+
+impl<I: IndexType> Iterator for Ring<'_, I> {
+    type Item = Id;
+
+    fn next(&mut self) -> Option<Id> {
+        let id = self.next?;
+        let next = self.set.next(id);
+        self.next = if next == self.start { None } else { Some(next) };
+        Some(id)
+    }
+}
+
+/// Formats the partition as a set of sets, like `{{0, 2}, {1}}`, with sets
+/// and indices in ascending order.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointSet;
+/// let mut set = DisjointSet::<u32>::with_size(3);
+/// set.union(2, 0);
+/// assert_eq!(format!("{:?}", set), "{{0, 2}, {1}}");
+/// ```
+impl<I: IndexType> fmt::Debug for DisjointSet<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sets = self.ordered_sets();
+        f.debug_set()
+            .entries(sets.iter().map(|set| DebugSet(set)))
+            .finish()
+    }
+}
+
+/// Formats a set's members like `{a, b}`.
+pub(crate) struct DebugSet<'a, T>(pub(crate) &'a [T]);
+
+impl<T: fmt::Debug> fmt::Debug for DebugSet<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0).finish()
+    }
+}
+
+impl<I: IndexType> Default for DisjointSet<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,191 @@
+use crate::disjoint_hash_set::DisjointHashSet;
+use crate::disjoint_set::{Id, IndexType};
+use indexmap::map::raw_entry_v1::{RawEntryApiV1, RawEntryMut};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+/// A view into a single value of a `DisjointHashSet`, which may either be
+/// present or not.
+///
+/// Created by [`DisjointHashSet::entry`].
+pub enum Entry<'a, T: Hash + Eq, S = RandomState, I = usize> {
+    /// The value is present.
+    Occupied(OccupiedEntry<'a, T, S, I>),
+    /// The value is not present.
+    Vacant(VacantEntry<'a, T, S, I>),
+}
+
+/// A view into a value that is present in a `DisjointHashSet`.
+pub struct OccupiedEntry<'a, T: Hash + Eq, S = RandomState, I = usize> {
+    set: &'a mut DisjointHashSet<T, S, I>,
+    id: Id,
+}
+
+/// A view into a value that is not present in a `DisjointHashSet`.
+pub struct VacantEntry<'a, T: Hash + Eq, S = RandomState, I = usize> {
+    set: &'a mut DisjointHashSet<T, S, I>,
+    value: T,
+    hash: u64,
+}
+
+impl<T: Hash + Eq, S: BuildHasher, I: IndexType> DisjointHashSet<T, S, I> {
+    /// Gets the entry for a value, to find or insert it with a single lookup.
+    ///
+    /// # Panics
+    ///
+    /// Inserting through a vacant entry panics if the index type `I` has no
+    /// room for another value, leaving the set unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::new();
+    /// set.insert("this");
+    /// let id = set.entry("that").or_insert_into(&"this");
+    /// assert_eq!(set.find(&"this"), Some(id));
+    /// assert_eq!(set.entry("that").or_insert(), id);
+    /// ```
+    pub fn entry(&mut self, value: T) -> Entry<'_, T, S, I> {
+        let hash = self.values.hasher().hash_one(&value);
+        match self
+            .values
+            .raw_entry_v1()
+            .index_from_hash(hash, |other| *other == value)
+        {
+            Some(index) => Entry::Occupied(OccupiedEntry {
+                set: self,
+                id: Id(index),
+            }),
+            None => Entry::Vacant(VacantEntry {
+                set: self,
+                value,
+                hash,
+            }),
+        }
+    }
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher, I: IndexType> Entry<'a, T, S, I> {
+    /// Returns the value of the entry.
+    pub fn value(&self) -> &T {
+        match self {
+            Entry::Occupied(entry) => entry.value(),
+            Entry::Vacant(entry) => entry.value(),
+        }
+    }
+
+    /// Find the set of the value, inserting it in its own set if not present.
+    pub fn or_insert(self) -> Id {
+        match self {
+            Entry::Occupied(mut entry) => entry.root(),
+            Entry::Vacant(entry) => entry.insert(),
+        }
+    }
+
+    /// Find the set of the value, inserting it into the set of `other` if not
+    /// present.
+    ///
+    /// If `other` isn't present either the value is inserted in its own set.
+    pub fn or_insert_into<Q>(self, other: &Q) -> Id
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        match self {
+            Entry::Occupied(mut entry) => entry.root(),
+            Entry::Vacant(entry) => entry.insert_into(other),
+        }
+    }
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher, I: IndexType> OccupiedEntry<'a, T, S, I> {
+    /// Returns the value of the entry.
+    pub fn value(&self) -> &T {
+        self.set.value_of(self.id).unwrap()
+    }
+
+    /// Find the set the value is in.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointHashSet, Entry};
+    /// let mut set = DisjointHashSet::new();
+    /// set.union("this", "that");
+    /// let id = set.find(&"that");
+    /// if let Entry::Occupied(mut entry) = set.entry("this") {
+    ///     assert_eq!(Some(entry.root()), id);
+    ///     assert_eq!(entry.set_size(), 2);
+    /// }
+    /// ```
+    pub fn root(&mut self) -> Id {
+        self.set.compress_path(self.id)
+    }
+
+    /// Returns the number of values in the same set as the value.
+    pub fn set_size(&self) -> usize {
+        self.set.set_size_of(self.id)
+    }
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher, I: IndexType> VacantEntry<'a, T, S, I> {
+    /// Returns the value of the entry.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Take ownership of the value without inserting it.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Insert the value in its own set, returning the id of the new set.
+    pub fn insert(self) -> Id {
+        self.insert_entry().1
+    }
+
+    /// Insert the value into the set of `other`, returning the id of that set.
+    ///
+    /// If `other` isn't present the value is inserted in its own set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointHashSet, Entry};
+    /// let mut set = DisjointHashSet::new();
+    /// set.insert("this");
+    /// if let Entry::Vacant(entry) = set.entry("that") {
+    ///     entry.insert_into(&"this");
+    /// }
+    /// assert!(set.connected(&"this", &"that"));
+    /// ```
+    pub fn insert_into<Q>(self, other: &Q) -> Id
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let other = self.set.find(other);
+        let (set, id) = self.insert_entry();
+        match other {
+            Some(other) => set.union_inner(id, other).root(),
+            None => id,
+        }
+    }
+
+    /// Insert the value in its own set, handing back the disjoint set.
+    fn insert_entry(self) -> (&'a mut DisjointHashSet<T, S, I>, Id) {
+        let VacantEntry { set, value, hash } = self;
+        set.set.assert_room();
+        match set
+            .values
+            .raw_entry_mut_v1()
+            .from_hash(hash, |other| *other == value)
+        {
+            RawEntryMut::Vacant(entry) => {
+                entry.insert_hashed_nocheck(hash, value, ());
+            }
+            RawEntryMut::Occupied(_) => unreachable!("vacant entry became occupied"),
+        }
+        let id = set.set.push_node();
+        (set, id)
+    }
+}
@@ -1,2 +1,16 @@
+mod concurrent_disjoint_hash_set;
+mod disjoint_hash_map;
 mod disjoint_hash_set;
-pub use crate::disjoint_hash_set::DisjointHashSet;
+mod disjoint_set;
+mod entry;
+mod rollback_disjoint_hash_set;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod snapshot;
+pub use crate::concurrent_disjoint_hash_set::ConcurrentDisjointHashSet;
+pub use crate::disjoint_hash_map::{DisjointHashMap, Merge};
+pub use crate::disjoint_hash_set::{DisjointHashSet, Members};
+pub use crate::disjoint_set::{DisjointSet, Id, IndexType, Union, UnionBy};
+pub use crate::entry::{Entry, OccupiedEntry, VacantEntry};
+pub use crate::rollback_disjoint_hash_set::{Checkpoint, RollbackDisjointHashSet};
+pub use crate::snapshot::{SnapshotError, SnapshotValue};
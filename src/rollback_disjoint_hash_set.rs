@@ -0,0 +1,336 @@
+use crate::disjoint_set::{DebugSet, DisjointSet, Id, Union, UnionBy};
+use indexmap::IndexSet;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+/// A change that can be undone.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Undo {
+    /// The last value was inserted.
+    Insert,
+    /// `child`, with a set of `child_size`, was linked under `root`, which
+    /// had rank `rank` before.
+    Link {
+        child: Id,
+        root: Id,
+        child_size: usize,
+        rank: u8,
+    },
+}
+
+/// A point a `RollbackDisjointHashSet` can be rolled back to.
+///
+/// Created by [`RollbackDisjointHashSet::snapshot`], and consumed by either
+/// rolling back to it or committing it. Dropping an inner checkpoint instead
+/// commits it once an enclosing one is closed, but until then every change
+/// is logged; so the outermost checkpoint must always be closed.
+#[must_use]
+#[derive(Debug)]
+pub struct Checkpoint {
+    undo_len: usize,
+    /// identifies the snapshot among the open ones
+    id: u64,
+}
+
+/// A disjoint set whose unions and inserts can be rolled back.
+///
+/// Sets are unioned by rank without path compression, so every change is a
+/// single parent pointer that can be reset cheaply, while `find` stays
+/// logarithmic. Changes are only logged while a snapshot is open.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::RollbackDisjointHashSet;
+/// let mut set = RollbackDisjointHashSet::new();
+/// set.union("this", "that");
+///
+/// let checkpoint = set.snapshot();
+/// set.union("that", "other");
+/// assert!(set.connected(&"this", &"other"));
+///
+/// set.rollback_to(checkpoint);
+/// assert!(set.connected(&"this", &"that"));
+/// assert!(!set.contains(&"other"));
+/// ```
+pub struct RollbackDisjointHashSet<T: Hash + Eq, S = RandomState> {
+    values: IndexSet<T, S>,
+    set: DisjointSet,
+    log: Vec<Undo>,
+    /// the ids of the open snapshots, innermost last
+    open: Vec<u64>,
+    /// the number of snapshots ever taken, for the id of the next one
+    taken: u64,
+}
+
+impl<T: Hash + Eq> RollbackDisjointHashSet<T> {
+    /// Create an empty `RollbackDisjointHashSet`.
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> RollbackDisjointHashSet<T, S> {
+    /// Create an empty `RollbackDisjointHashSet` which will use the given
+    /// hash builder to hash values.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            values: IndexSet::with_hasher(hash_builder),
+            set: DisjointSet::with_union_by(UnionBy::Rank),
+            log: Vec::new(),
+            open: Vec::new(),
+            taken: 0,
+        }
+    }
+
+    /// Returns the number of elements in the disjoint set.
+    pub fn size(&self) -> usize {
+        self.set.size()
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn num_sets(&self) -> usize {
+        self.set.num_sets()
+    }
+
+    /// Returns `true` if the disjoint set contains the specified value.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.values.contains(value)
+    }
+
+    /// Insert a new value into the disjoint set.
+    ///
+    /// If the disjoint set already had this value present, returns `false`.
+    /// If not returns `true`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::RollbackDisjointHashSet;
+    /// let mut set = RollbackDisjointHashSet::new();
+    /// assert!(set.insert("this"));
+    /// assert!(!set.insert("this"));
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        let len = self.size();
+        self.insert_inner(value).0 == len
+    }
+
+    fn insert_inner(&mut self, value: T) -> Id {
+        let (index, inserted) = self.values.insert_full(value);
+        if inserted {
+            self.set.push_node();
+            self.record(Undo::Insert);
+        }
+        Id(index)
+    }
+
+    /// Find the set a value is in.
+    ///
+    /// Returns `None` if the value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::RollbackDisjointHashSet;
+    /// let mut set = RollbackDisjointHashSet::new();
+    /// assert!(set.find(&"this").is_none());
+    /// set.union("this", "that");
+    /// assert_eq!(set.find(&"this"), set.find(&"that"));
+    /// ```
+    pub fn find<Q>(&self, value: &Q) -> Option<Id>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let id = Id(self.values.get_index_of(value)?);
+        Some(self.set.root(id))
+    }
+
+    /// Returns the number of values in the same set as a value.
+    ///
+    /// Returns `None` if the value is not present.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::RollbackDisjointHashSet;
+    /// let mut set = RollbackDisjointHashSet::new();
+    /// set.union("this", "that");
+    /// assert_eq!(set.set_size(&"this"), Some(2));
+    /// assert_eq!(set.set_size(&"other"), None);
+    /// ```
+    pub fn set_size<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let id = Id(self.values.get_index_of(value)?);
+        Some(self.set.set_size_of(id))
+    }
+
+    /// Returns `true` if the two values are in the same set.
+    pub fn connected<Q>(&self, value: &Q, other: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let value = self.find(value);
+        value.is_some() && value == self.find(other)
+    }
+
+    /// Unions two sets together specified by values, inserting them if not
+    /// present.
+    ///
+    /// Returns whether the sets were merged or were already the same set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{RollbackDisjointHashSet, Union};
+    /// let mut set = RollbackDisjointHashSet::new();
+    /// assert!(set.union("this", "that").is_merged());
+    /// let root = set.find(&"this").unwrap();
+    /// assert_eq!(set.union("that", "this"), Union::AlreadyConnected { root });
+    /// ```
+    pub fn union(&mut self, value: T, other: T) -> Union {
+        let value = self.insert_inner(value);
+        let other = self.insert_inner(other);
+        let value = self.set.root(value);
+        let other = self.set.root(other);
+        let nodes = (self.set.get(value), self.set.get(other));
+        let union = self.set.union_inner(value, other);
+        if let Union::Merged { root, absorbed, .. } = union {
+            let (root_node, child_node) = if root == value {
+                nodes
+            } else {
+                (nodes.1, nodes.0)
+            };
+            self.record(Undo::Link {
+                child: absorbed,
+                root,
+                child_size: child_node.size,
+                rank: root_node.rank,
+            });
+        }
+        union
+    }
+
+    fn record(&mut self, undo: Undo) {
+        if !self.open.is_empty() {
+            self.log.push(undo);
+        }
+    }
+
+    /// Start recording changes so they can be rolled back.
+    ///
+    /// Snapshots can be nested, but must be rolled back or committed in the
+    /// reverse order they were taken. Closing a snapshot closes any inner
+    /// ones that were dropped, keeping their changes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::RollbackDisjointHashSet;
+    /// let mut set = RollbackDisjointHashSet::new();
+    /// let outer = set.snapshot();
+    /// set.union(1, 2);
+    /// let inner = set.snapshot();
+    /// set.union(2, 3);
+    /// set.commit(inner);
+    /// assert!(set.connected(&1, &3));
+    ///
+    /// set.rollback_to(outer);
+    /// assert_eq!(set.size(), 0);
+    /// ```
+    pub fn snapshot(&mut self) -> Checkpoint {
+        let id = self.taken;
+        self.taken += 1;
+        self.open.push(id);
+        Checkpoint {
+            undo_len: self.log.len(),
+            id,
+        }
+    }
+
+    /// Undo every insert and union made since the checkpoint was taken.
+    ///
+    /// # Panics
+    /// Panics if an enclosing snapshot was already closed.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) {
+        let depth = self.depth(&checkpoint, "rolled back");
+        while self.log.len() > checkpoint.undo_len {
+            match self.log.pop().unwrap() {
+                Undo::Insert => {
+                    self.values.pop();
+                    self.set.swap_remove(Id(self.values.len()));
+                }
+                Undo::Link {
+                    child,
+                    root,
+                    child_size,
+                    rank,
+                } => self.set.unlink(root, child, child_size, rank),
+            }
+        }
+        self.close(depth);
+    }
+
+    /// Keep the changes made since the checkpoint was taken.
+    ///
+    /// They can still be rolled back by an enclosing snapshot.
+    ///
+    /// # Panics
+    /// Panics if an enclosing snapshot was already closed.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        let depth = self.depth(&checkpoint, "committed");
+        self.close(depth);
+    }
+
+    /// The number of snapshots open outside the checkpoint's.
+    fn depth(&self, checkpoint: &Checkpoint, action: &str) -> usize {
+        match self.open.iter().rposition(|&id| id == checkpoint.id) {
+            Some(depth) => depth,
+            None => panic!("checkpoint {} out of order", action),
+        }
+    }
+
+    /// Close the snapshot at `depth` along with any inner ones left open.
+    fn close(&mut self, depth: usize) {
+        self.open.truncate(depth);
+        if self.open.is_empty() {
+            self.log.clear();
+        }
+    }
+}
+
+/// Formats the partition as a set of sets, like `{{"this", "that"}, {"other"}}`,
+/// in the same order as a `DisjointHashSet`.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::RollbackDisjointHashSet;
+/// let mut set = RollbackDisjointHashSet::new();
+/// set.union("this", "that");
+/// set.insert("other");
+/// assert_eq!(format!("{:?}", set), r#"{{"this", "that"}, {"other"}}"#);
+/// ```
+impl<T: Hash + Eq + fmt::Debug, S> fmt::Debug for RollbackDisjointHashSet<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = |set: Vec<usize>| -> Vec<&T> {
+            set.into_iter()
+                .filter_map(|i| self.values.get_index(i))
+                .collect()
+        };
+        let sets: Vec<_> = self.set.ordered_sets().into_iter().map(values).collect();
+        f.debug_set()
+            .entries(sets.iter().map(|set| DebugSet(set)))
+            .finish()
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> Default for RollbackDisjointHashSet<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
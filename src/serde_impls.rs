@@ -0,0 +1,81 @@
+use crate::disjoint_set::DisjointSet;
+use crate::{DisjointHashSet, IndexType};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::hash::{BuildHasher, Hash};
+
+/// Serializes the partition as a list of sets.
+///
+/// Each set is sorted and the sets are ordered by their smallest value, so
+/// the output only depends on the partition and not on the hasher or the
+/// order values were inserted and unioned in.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashSet;
+/// let mut set = DisjointHashSet::new();
+/// set.insert_set(vec!["this", "that"]);
+/// set.insert("other");
+/// assert_eq!(
+///     serde_json::to_string(&set).unwrap(),
+///     r#"[["other"],["that","this"]]"#
+/// );
+/// ```
+impl<T, S, I> Serialize for DisjointHashSet<T, S, I>
+where
+    T: Hash + Eq + Ord + Serialize,
+    S: BuildHasher,
+    I: IndexType,
+{
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let mut sets: Vec<Vec<&T>> = self.sets().collect();
+        sets.iter_mut().for_each(|set| set.sort());
+        sets.sort();
+        serializer.collect_seq(sets)
+    }
+}
+
+/// Deserializes a partition from a list of sets.
+///
+/// Sets which share a value are unioned, as with `insert_set`. More values
+/// than the index type has room for are an error.
+///
+/// # Examples
+/// ```
+/// # use disjoint_hash_set::DisjointHashSet;
+/// use std::collections::hash_map::RandomState;
+///
+/// let mut set: DisjointHashSet<String> =
+///     serde_json::from_str(r#"[["this","that"],["other"]]"#).unwrap();
+/// assert!(set.connected("this", "that"));
+/// assert!(!set.connected("this", "other"));
+///
+/// let singletons: Vec<[u32; 1]> = (0..200).map(|i| [i]).collect();
+/// let json = serde_json::to_string(&singletons).unwrap();
+/// assert!(serde_json::from_str::<DisjointHashSet<u32, RandomState, u8>>(&json).is_err());
+/// ```
+impl<'de, T, S, I> Deserialize<'de> for DisjointHashSet<T, S, I>
+where
+    T: Hash + Eq + Deserialize<'de>,
+    S: BuildHasher + Default,
+    I: IndexType,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut set = Self::default();
+        for values in Vec::<Vec<T>>::deserialize(deserializer)? {
+            // like insert_set, but checking there is room for each new value
+            let mut root = None;
+            for value in values {
+                if set.size() >= DisjointSet::<I>::max_size() && !set.contains(&value) {
+                    return Err(D::Error::custom("too many values for the index type"));
+                }
+                let id = set.find_or_insert(value);
+                root = Some(match root {
+                    Some(root) => set.union_sets(root, id).root(),
+                    None => id,
+                });
+            }
+        }
+        Ok(set)
+    }
+}
@@ -0,0 +1,372 @@
+use crate::disjoint_hash_set::DisjointHashSet;
+use crate::disjoint_set::{DisjointSet, Id, IndexType, Node, UnionBy};
+use indexmap::IndexMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::io::{self, Read, Write};
+
+const MAGIC: [u8; 4] = *b"DHSS";
+const VERSION: u32 = 2;
+
+/// An error reading a snapshot of a `DisjointHashSet`.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading failed or the snapshot was truncated.
+    Io(io::Error),
+    /// The data isn't a snapshot.
+    InvalidMagic,
+    /// The snapshot was written in an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The checksum doesn't match the contents of the snapshot.
+    ChecksumMismatch,
+    /// The contents of the snapshot passed the checksum but don't decode to
+    /// a valid disjoint set.
+    Corrupt,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read snapshot: {}", err),
+            Self::InvalidMagic => write!(f, "not a disjoint set snapshot"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            Self::ChecksumMismatch => write!(f, "snapshot checksum mismatch"),
+            Self::Corrupt => write!(f, "snapshot is corrupt"),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// A value that can be written to and read from a binary snapshot.
+///
+/// Implemented for integers, `String` and `Vec<u8>`. Integers are written
+/// little endian, with `usize` and `isize` widened to 64 bits so snapshots
+/// are portable.
+pub trait SnapshotValue: Sized {
+    /// Write the value.
+    fn write_value<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Read a value written by `write_value`.
+    fn read_value<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+macro_rules! impl_snapshot_value_int {
+    ($($int:ty),*) => {$(
+        impl SnapshotValue for $int {
+            fn write_value<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                writer.write_all(&self.to_le_bytes())
+            }
+
+            fn read_value<R: Read>(reader: &mut R) -> io::Result<Self> {
+                let mut bytes = [0; std::mem::size_of::<$int>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(<$int>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+impl_snapshot_value_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl SnapshotValue for usize {
+    fn write_value<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (*self as u64).write_value(writer)
+    }
+
+    fn read_value<R: Read>(reader: &mut R) -> io::Result<Self> {
+        usize::try_from(u64::read_value(reader)?).map_err(invalid_data)
+    }
+}
+
+impl SnapshotValue for isize {
+    fn write_value<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (*self as i64).write_value(writer)
+    }
+
+    fn read_value<R: Read>(reader: &mut R) -> io::Result<Self> {
+        isize::try_from(i64::read_value(reader)?).map_err(invalid_data)
+    }
+}
+
+impl SnapshotValue for Vec<u8> {
+    fn write_value<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.len().write_value(writer)?;
+        writer.write_all(self)
+    }
+
+    fn read_value<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let len = u64::read_value(reader)?;
+        // read through `take` so a corrupt length can't allocate up front
+        let mut bytes = Vec::new();
+        reader.take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(bytes)
+    }
+}
+
+impl SnapshotValue for String {
+    fn write_value<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.len().write_value(writer)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn read_value<R: Read>(reader: &mut R) -> io::Result<Self> {
+        String::from_utf8(Vec::read_value(reader)?).map_err(invalid_data)
+    }
+}
+
+/// Write the low `width` bytes of an index.
+fn write_index<W: Write>(writer: &mut W, index: u64, width: usize) -> io::Result<()> {
+    writer.write_all(&index.to_le_bytes()[..width])
+}
+
+/// Read an index written by `write_index`.
+fn read_index<R: Read>(reader: &mut R, width: usize) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes[..width])?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn invalid_data<E: Error + Send + Sync + 'static>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Wraps a reader or writer, keeping a FNV-1a hash of the bytes passed through.
+struct Checksum<T> {
+    inner: T,
+    hash: u64,
+}
+
+impl<T> Checksum<T> {
+    fn new(inner: T) -> Self {
+        Self {
+            inner,
+            hash: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash ^= byte as u64;
+            self.hash = self.hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl<W: Write> Write for Checksum<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<R: Read> Read for Checksum<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<T, S, I> DisjointHashSet<T, S, I>
+where
+    T: Hash + Eq + SnapshotValue,
+    S: BuildHasher,
+    I: IndexType,
+{
+    /// Write a binary snapshot of the disjoint set.
+    ///
+    /// The snapshot holds the internal layout of the sets alongside the
+    /// values, so reading it back doesn't have to redo any unions. After a
+    /// versioned header comes the length of the contents, the contents, and
+    /// a checksum of everything before it. Each value takes two indices of
+    /// the width of `I`, plus a rank byte when unioning by rank, and a root
+    /// stores its set's size in place of its parent as it does in memory.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::DisjointHashSet;
+    /// let mut set = DisjointHashSet::<u32>::new();
+    /// set.insert_set(vec![1, 2, 3]);
+    /// set.insert(4);
+    ///
+    /// let mut snapshot = Vec::new();
+    /// set.write_snapshot(&mut snapshot).unwrap();
+    /// let mut set = DisjointHashSet::<u32>::read_snapshot(&snapshot[..]).unwrap();
+    /// assert!(set.connected(&1, &3));
+    /// assert!(!set.connected(&1, &4));
+    /// ```
+    pub fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        // the contents are framed by their length, so they're written out
+        // in full before the header
+        let mut contents = Vec::new();
+        let width = std::mem::size_of::<I>();
+        let union_by: u8 = match self.set.union_by {
+            UnionBy::Size => 0,
+            UnionBy::Rank => 1,
+        };
+        union_by.write_value(&mut contents)?;
+        (width as u8).write_value(&mut contents)?;
+        self.size().write_value(&mut contents)?;
+        self.num_sets().write_value(&mut contents)?;
+        let mark = root_mark(width);
+        for i in 0..self.size() {
+            let node = self.set.get(Id(i));
+            let link = if node.parent.0 == i {
+                mark + node.size as u64
+            } else {
+                node.parent.0 as u64
+            };
+            write_index(&mut contents, link, width)?;
+            write_index(&mut contents, node.next.0 as u64, width)?;
+            if self.set.union_by == UnionBy::Rank {
+                node.rank.write_value(&mut contents)?;
+            }
+        }
+        for value in self.values.keys() {
+            value.write_value(&mut contents)?;
+        }
+
+        let mut writer = Checksum::new(writer);
+        writer.write_all(&MAGIC)?;
+        VERSION.write_value(&mut writer)?;
+        contents.len().write_value(&mut writer)?;
+        writer.write_all(&contents)?;
+        let hash = writer.hash;
+        hash.write_value(&mut writer.inner)
+    }
+
+    /// Read a binary snapshot written by `write_snapshot`.
+    ///
+    /// The checksum is checked before any of the contents are decoded, so
+    /// a damaged snapshot gives `ChecksumMismatch` rather than a decoding
+    /// error. Damage to the length of the contents reads as truncation.
+    ///
+    /// # Errors
+    /// Returns an error if reading fails, or if the snapshot is from an
+    /// incompatible version of the format or is corrupt.
+    ///
+    /// # Examples
+    /// ```
+    /// # use disjoint_hash_set::{DisjointHashSet, SnapshotError};
+    /// let set = DisjointHashSet::<String>::with_values(vec!["this".to_string()]);
+    /// let mut snapshot = Vec::new();
+    /// set.write_snapshot(&mut snapshot).unwrap();
+    ///
+    /// snapshot[20] ^= 1;
+    /// let err = DisjointHashSet::<String>::read_snapshot(&snapshot[..]).unwrap_err();
+    /// assert!(matches!(err, SnapshotError::ChecksumMismatch));
+    /// ```
+    pub fn read_snapshot<R: Read>(reader: R) -> Result<Self, SnapshotError>
+    where
+        S: Default,
+    {
+        let mut reader = Checksum::new(reader);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(SnapshotError::InvalidMagic);
+        }
+        let version = u32::read_value(&mut reader)?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        // read through `take` so a corrupt length can't allocate up front
+        let len = u64::read_value(&mut reader)?;
+        let mut contents = Vec::new();
+        (&mut reader).take(len).read_to_end(&mut contents)?;
+        if contents.len() as u64 != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let hash = reader.hash;
+        if u64::read_value(&mut reader.inner)? != hash {
+            return Err(SnapshotError::ChecksumMismatch);
+        }
+        // the checksum matched, so anything that doesn't decode is corrupt
+        Self::read_contents(&contents).ok_or(SnapshotError::Corrupt)
+    }
+
+    fn read_contents(mut contents: &[u8]) -> Option<Self>
+    where
+        S: Default,
+    {
+        let reader = &mut contents;
+        let union_by = match u8::read_value(reader).ok()? {
+            0 => UnionBy::Size,
+            1 => UnionBy::Rank,
+            _ => return None,
+        };
+        let width = usize::from(u8::read_value(reader).ok()?);
+        if ![1, 2, 4, 8].contains(&width) {
+            return None;
+        }
+        let len = usize::read_value(reader).ok()?;
+        let num_sets = usize::read_value(reader).ok()?;
+
+        // grow as nodes are read rather than trusting `len` up front
+        let mark = root_mark(width);
+        let mut data = Vec::new();
+        for i in 0..len {
+            let link = read_index(reader, width).ok()?;
+            let next = Id(usize::try_from(read_index(reader, width).ok()?).ok()?);
+            let rank = match union_by {
+                UnionBy::Size => 0,
+                UnionBy::Rank => u8::read_value(reader).ok()?,
+            };
+            let (parent, size) = if link >= mark {
+                (Id(i), usize::try_from(link - mark).ok()?)
+            } else {
+                (Id(usize::try_from(link).ok()?), 1)
+            };
+            data.push(Node {
+                size,
+                rank,
+                parent,
+                next,
+            });
+        }
+        let mut values = IndexMap::with_hasher(S::default());
+        for _ in 0..len {
+            if values.insert(T::read_value(reader).ok()?, ()).is_some() {
+                return None;
+            }
+        }
+        if !reader.is_empty() {
+            return None;
+        }
+        // checks the structure of the sets, including that a snapshot of a
+        // set with wider indices fits
+        let set = DisjointSet::from_nodes(data, num_sets, union_by)?;
+        Some(Self { values, set })
+    }
+}
+
+/// Links from this mark up belong to roots, for indices `width` bytes wide,
+/// matching `DisjointSet` in memory.
+fn root_mark(width: usize) -> u64 {
+    1 << (width * 8 - 1)
+}
@@ -0,0 +1,92 @@
+//! Reproducible operations and a naive partition to check disjoint sets
+//! against, shared by the integration tests.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+/// Small xorshift generator so the operation sequences are reproducible.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// Naive partition model: every value is labelled with the id of its set.
+#[derive(Clone, Default)]
+pub struct Model {
+    pub labels: HashMap<u64, usize>,
+    next_label: usize,
+}
+
+impl Model {
+    fn fresh(&mut self) -> usize {
+        self.next_label += 1;
+        self.next_label
+    }
+
+    pub fn insert(&mut self, value: u64) -> usize {
+        if let Some(&label) = self.labels.get(&value) {
+            return label;
+        }
+        let label = self.fresh();
+        self.labels.insert(value, label);
+        label
+    }
+
+    pub fn union(&mut self, value: u64, other: u64) -> bool {
+        let from = self.insert(other);
+        let to = self.insert(value);
+        self.labels
+            .values_mut()
+            .filter(|l| **l == from)
+            .for_each(|l| *l = to);
+        from != to
+    }
+
+    pub fn split(&mut self, value: u64) {
+        let label = self.fresh();
+        self.labels.insert(value, label);
+    }
+
+    pub fn split_into(&mut self, value: u64, into: u64) {
+        self.split(value);
+        let label = self.insert(into);
+        self.labels.insert(value, label);
+    }
+
+    pub fn members(&self, value: u64) -> Vec<u64> {
+        let label = self.labels[&value];
+        let mut members: Vec<u64> = self
+            .labels
+            .iter()
+            .filter(|(_, l)| **l == label)
+            .map(|(v, _)| *v)
+            .collect();
+        members.sort();
+        members
+    }
+
+    pub fn num_sets(&self) -> usize {
+        let mut labels: Vec<usize> = self.labels.values().copied().collect();
+        labels.sort();
+        labels.dedup();
+        labels.len()
+    }
+
+    /// Whether both values are present and in the same set.
+    pub fn connected(&self, value: u64, other: u64) -> bool {
+        match (self.labels.get(&value), self.labels.get(&other)) {
+            (Some(value), Some(other)) => value == other,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use disjoint_hash_set::ConcurrentDisjointHashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+const THREADS: usize = 8;
+
+/// Deterministic shuffle so every thread sees edges from all over the graph.
+fn shuffle<T>(items: &mut [T]) {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        items.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
+#[test]
+fn unions_from_many_threads() {
+    const VALUES: u64 = 100_000;
+    const GROUPS: u64 = 64;
+
+    let mut edges: Vec<(u64, u64)> = (0..VALUES - GROUPS).map(|i| (i, i + GROUPS)).collect();
+    shuffle(&mut edges);
+
+    let set = ConcurrentDisjointHashSet::new();
+    thread::scope(|s| {
+        for chunk in edges.chunks(edges.len() / THREADS + 1) {
+            let set = &set;
+            s.spawn(move || {
+                for &(a, b) in chunk {
+                    set.union(a, b);
+                }
+            });
+        }
+    });
+
+    assert_eq!(set.size(), VALUES as usize);
+    assert_eq!(set.num_sets(), GROUPS as usize);
+    for value in 0..VALUES {
+        assert_eq!(set.find(&value), set.find(&(value % GROUPS)));
+        assert!(!set.connected(&value, &((value + 1) % VALUES)));
+    }
+}
+
+#[test]
+fn merges_are_counted_once() {
+    const VALUES: u64 = 10_000;
+
+    // every thread races to union the same chain, only one can win each merge
+    let merged = AtomicUsize::new(0);
+    let set = ConcurrentDisjointHashSet::new();
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for value in 1..VALUES {
+                    if set.union(value - 1, value) {
+                        merged.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(merged.into_inner(), VALUES as usize - 1);
+    assert_eq!(set.num_sets(), 1);
+    assert!(set.connected(&0, &(VALUES - 1)));
+}
+
+#[test]
+fn inserts_from_many_threads() {
+    const VALUES: u64 = 50_000;
+
+    let inserted = AtomicUsize::new(0);
+    let set = ConcurrentDisjointHashSet::new();
+    thread::scope(|s| {
+        for t in 0..THREADS as u64 {
+            let (set, inserted) = (&set, &inserted);
+            s.spawn(move || {
+                // overlapping ranges so threads race on the same values
+                for value in (t * VALUES / 16)..VALUES {
+                    if set.insert(value) {
+                        inserted.fetch_add(1, Ordering::Relaxed);
+                    }
+                    assert!(set.find(&value).is_some());
+                }
+            });
+        }
+    });
+
+    assert_eq!(inserted.into_inner(), VALUES as usize);
+    assert_eq!(set.size(), VALUES as usize);
+    assert_eq!(set.num_sets(), VALUES as usize);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn from_edges_par_matches_union_loop() {
+    use disjoint_hash_set::DisjointHashSet;
+
+    const VALUES: u64 = 20_000;
+
+    // every value shows up in several edges, some of them twice in one
+    let mut edges: Vec<(u64, u64)> = (0..3 * VALUES)
+        .map(|i| (i % VALUES, i * 7 % VALUES / 3))
+        .collect();
+    shuffle(&mut edges);
+
+    let mut looped = DisjointHashSet::new();
+    for &(value, other) in &edges {
+        looped.union(value, other);
+    }
+    let set = DisjointHashSet::from_edges_par(edges);
+    assert_eq!(set, looped);
+    assert_eq!(set.num_sets(), looped.num_sets());
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::{Model, Rng};
+use disjoint_hash_set::{DisjointHashSet, IndexType, UnionBy};
+use std::collections::hash_map::RandomState;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+fn check<I: IndexType>(set: &mut DisjointHashSet<u64, RandomState, I>, model: &Model) {
+    assert_eq!(set.size(), model.labels.len());
+    assert_eq!(set.num_sets(), model.num_sets());
+    assert_eq!(set.sets().count(), model.num_sets());
+
+    for &value in model.labels.keys() {
+        let mut members: Vec<u64> = set.members(&value).copied().collect();
+        members.sort();
+        assert_eq!(members, model.members(value));
+        assert_eq!(set.set_size(&value), Some(members.len()));
+        for &other in model.labels.keys() {
+            assert_eq!(set.connected(&value, &other), model.connected(value, other));
+        }
+    }
+}
+
+fn run<I: IndexType>(mut rng: Rng, mut set: DisjointHashSet<u64, RandomState, I>) {
+    let mut model = Model::default();
+    for _ in 0..200 {
+        let value = rng.below(24);
+        let other = rng.below(24);
+        match rng.below(6) {
+            0 => {
+                assert_eq!(set.insert(value), !model.labels.contains_key(&value));
+                model.insert(value);
+            }
+            1 | 2 => {
+                let union = set.union(value, other);
+                assert_eq!(union.is_merged(), model.union(value, other));
+                assert_eq!(set.find(&value), Some(union.root()));
+            }
+            3 => {
+                set.split(value);
+                model.split(value);
+            }
+            4 => {
+                set.split_into(value, other);
+                model.split_into(value, other);
+            }
+            _ => {
+                assert_eq!(
+                    set.remove(&value),
+                    model.labels.remove(&value).map(|_| value)
+                );
+            }
+        }
+        check(&mut set, &model);
+    }
+}
+
+#[test]
+fn matches_naive_partition() {
+    for seed in 1..=64u64 {
+        let rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let union_by = if seed % 2 == 0 {
+            UnionBy::Size
+        } else {
+            UnionBy::Rank
+        };
+        if seed % 4 < 2 {
+            run(rng, DisjointHashSet::<_>::with_union_by(union_by));
+        } else {
+            run(rng, DisjointHashSet::<_, _, u8>::with_union_by(union_by));
+        }
+    }
+}
+
+#[test]
+fn full_index_type_panics_without_inserting() {
+    let mut set = DisjointHashSet::<u64, RandomState, u8>::default();
+    for value in 0..127 {
+        set.insert(value);
+    }
+    let full = set.clone();
+    let insert = catch_unwind(AssertUnwindSafe(|| set.insert(127)));
+    assert!(insert.is_err());
+    let entry = catch_unwind(AssertUnwindSafe(|| set.entry(127).or_insert()));
+    assert!(entry.is_err());
+    assert!(!set.contains(&127));
+    assert_eq!(set, full);
+    assert!(!set.insert(0));
+    assert!(set.union(0, 1).is_merged());
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{Model, Rng};
+use disjoint_hash_set::RollbackDisjointHashSet;
+
+fn check(set: &RollbackDisjointHashSet<u64>, model: &Model) {
+    assert_eq!(set.size(), model.labels.len());
+    assert_eq!(set.num_sets(), model.num_sets());
+    for value in 0..16 {
+        assert_eq!(set.contains(&value), model.labels.contains_key(&value));
+        let size = model.labels.get(&value).map(|_| model.members(value).len());
+        assert_eq!(set.set_size(&value), size);
+        for other in 0..16 {
+            assert_eq!(set.connected(&value, &other), model.connected(value, other));
+        }
+    }
+}
+
+#[test]
+fn rollback_restores_nested_snapshots() {
+    for seed in 1..=64u64 {
+        let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let mut set = RollbackDisjointHashSet::new();
+        let mut model = Model::default();
+        // each open checkpoint with the model from when it was taken
+        let mut open = Vec::new();
+
+        for _ in 0..300 {
+            let value = rng.below(16);
+            let other = rng.below(16);
+            match rng.below(8) {
+                0 | 1 => {
+                    assert_eq!(set.insert(value), !model.labels.contains_key(&value));
+                    model.insert(value);
+                }
+                2..=4 => assert_eq!(
+                    set.union(value, other).is_merged(),
+                    model.union(value, other)
+                ),
+                5 => open.push((set.snapshot(), model.clone())),
+                6 => {
+                    if let Some((checkpoint, saved)) = open.pop() {
+                        set.rollback_to(checkpoint);
+                        model = saved;
+                    }
+                }
+                _ => {
+                    if let Some((checkpoint, _)) = open.pop() {
+                        set.commit(checkpoint);
+                    }
+                }
+            }
+            check(&set, &model);
+        }
+
+        while let Some((checkpoint, saved)) = open.pop() {
+            set.rollback_to(checkpoint);
+            check(&set, &saved);
+        }
+    }
+}
+
+#[test]
+fn closing_a_snapshot_closes_dropped_inner_ones() {
+    let mut set = RollbackDisjointHashSet::new();
+    let outer = set.snapshot();
+    set.union(1, 2);
+    drop(set.snapshot());
+    set.union(2, 3);
+    set.rollback_to(outer);
+    assert_eq!(set.size(), 0);
+
+    let outer = set.snapshot();
+    drop(set.snapshot());
+    set.union(1, 2);
+    set.commit(outer);
+    let checkpoint = set.snapshot();
+    set.union(2, 3);
+    set.rollback_to(checkpoint);
+    assert!(set.connected(&1, &2));
+    assert!(!set.contains(&3));
+}
+
+#[test]
+#[should_panic(expected = "out of order")]
+fn rejects_checkpoint_after_enclosing_one_closed() {
+    let mut set = RollbackDisjointHashSet::new();
+    let outer = set.snapshot();
+    let inner = set.snapshot();
+    set.union(1, 2);
+    set.rollback_to(outer);
+    set.rollback_to(inner);
+}
@@ -0,0 +1,211 @@
+use disjoint_hash_set::{DisjointHashSet, SnapshotError, UnionBy};
+use std::collections::hash_map::RandomState;
+
+/// Bytes before the contents: magic, version and the length of the contents.
+const HEADER: usize = 4 + 4 + 8;
+/// Bytes before the first node: the header, union strategy, index width,
+/// length and number of sets.
+const NODES: usize = HEADER + 1 + 1 + 8 + 8;
+/// Bytes per node of a set indexed by `usize`: link and next.
+const NODE: usize = 8 + 8;
+/// Links from here up belong to roots of a set indexed by `usize`.
+const ROOT: u64 = 1 << 63;
+
+fn snapshot(set: &DisjointHashSet<u32>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    set.write_snapshot(&mut bytes).unwrap();
+    bytes
+}
+
+/// Overwrite a 64 bit field of a snapshot and fix up the checksum, so only
+/// the structural checks can catch the change.
+fn patch(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    fix_checksum(bytes);
+}
+
+fn field(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn fix_checksum(bytes: &mut [u8]) {
+    let end = bytes.len() - 8;
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &byte in &bytes[..end] {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    bytes[end..].copy_from_slice(&hash.to_le_bytes());
+}
+
+fn read(bytes: &[u8]) -> Result<DisjointHashSet<u32>, SnapshotError> {
+    DisjointHashSet::read_snapshot(bytes)
+}
+
+fn pair() -> DisjointHashSet<u32> {
+    let mut set = DisjointHashSet::new();
+    set.union(1, 2);
+    set.insert(3);
+    set
+}
+
+#[test]
+fn round_trips_after_removes() {
+    for union_by in [UnionBy::Size, UnionBy::Rank] {
+        let mut set = DisjointHashSet::<u32, RandomState, u8>::with_union_by(union_by);
+        for i in 0..40 {
+            set.union(i, i / 3);
+            set.union(i, i % 7);
+        }
+        for i in (0..40).step_by(3) {
+            set.remove(&i);
+        }
+        set.split(5);
+        let mut bytes = Vec::new();
+        set.write_snapshot(&mut bytes).unwrap();
+        let mut read = DisjointHashSet::<u32, RandomState, u8>::read_snapshot(&bytes[..]).unwrap();
+        assert_eq!(read, set);
+        read.union(5, 6);
+        set.union(5, 6);
+        assert_eq!(read, set);
+
+        let wide = DisjointHashSet::<u32>::read_snapshot(&bytes[..]).unwrap();
+        assert_eq!(wide.sets().count(), set.num_sets());
+    }
+}
+
+#[test]
+fn writes_indices_at_their_width() {
+    for (union_by, rank) in [(UnionBy::Size, 0), (UnionBy::Rank, 1)] {
+        let mut set = DisjointHashSet::<u32, RandomState, u32>::with_union_by(union_by);
+        for i in 0..100 {
+            set.union(i, i / 2);
+        }
+        let mut bytes = Vec::new();
+        set.write_snapshot(&mut bytes).unwrap();
+        // two u32 indices and maybe a rank per node, then a u32 per value
+        assert_eq!(bytes.len(), NODES + 100 * (4 + 4 + rank) + 100 * 4 + 8);
+    }
+}
+
+#[test]
+fn rejects_invalid_magic() {
+    let mut bytes = snapshot(&pair());
+    bytes[0] = b'X';
+    assert!(matches!(read(&bytes), Err(SnapshotError::InvalidMagic)));
+}
+
+#[test]
+fn rejects_unsupported_version() {
+    let mut bytes = snapshot(&pair());
+    bytes[4..8].copy_from_slice(&3u32.to_le_bytes());
+    assert!(matches!(
+        read(&bytes),
+        Err(SnapshotError::UnsupportedVersion(3))
+    ));
+}
+
+#[test]
+fn rejects_truncated_input() {
+    let bytes = snapshot(&pair());
+    for len in 0..bytes.len() {
+        assert!(
+            matches!(read(&bytes[..len]), Err(SnapshotError::Io(_))),
+            "truncated to {} bytes",
+            len
+        );
+    }
+}
+
+#[test]
+fn detects_flipped_bits_before_decoding() {
+    let mut set = DisjointHashSet::<String>::new();
+    set.union("this".to_string(), "that".to_string());
+    let mut bytes = Vec::new();
+    set.write_snapshot(&mut bytes).unwrap();
+    // everything after the length of the contents, which reads as truncated
+    // when damaged
+    for offset in HEADER..bytes.len() {
+        let mut bytes = bytes.clone();
+        bytes[offset] ^= 1;
+        assert!(
+            matches!(
+                DisjointHashSet::<String>::read_snapshot(&bytes[..]),
+                Err(SnapshotError::ChecksumMismatch)
+            ),
+            "flipped a bit at offset {}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn rejects_values_that_do_not_decode() {
+    let set = DisjointHashSet::<String>::with_values(vec!["this".to_string()]);
+    let mut bytes = Vec::new();
+    set.write_snapshot(&mut bytes).unwrap();
+    let end = bytes.len() - 8;
+    bytes[end - 1] = 0xff;
+    fix_checksum(&mut bytes);
+    assert!(matches!(
+        DisjointHashSet::<String>::read_snapshot(&bytes[..]),
+        Err(SnapshotError::Corrupt)
+    ));
+}
+
+#[test]
+fn rejects_parent_cycle() {
+    let mut bytes = snapshot(&DisjointHashSet::with_values(vec![1, 2]));
+    patch(&mut bytes, NODES, 1);
+    patch(&mut bytes, NODES + NODE, 0);
+    assert!(matches!(read(&bytes), Err(SnapshotError::Corrupt)));
+}
+
+#[test]
+fn rejects_broken_member_list() {
+    let mut bytes = snapshot(&pair());
+    for i in 0..3 {
+        patch(&mut bytes, NODES + i * NODE + 8, i as u64);
+    }
+    assert!(matches!(read(&bytes), Err(SnapshotError::Corrupt)));
+}
+
+#[test]
+fn rejects_wrong_set_size() {
+    let mut bytes = snapshot(&pair());
+    for i in 0..3 {
+        let offset = NODES + i * NODE;
+        if field(&bytes, offset) == ROOT + 2 {
+            patch(&mut bytes, offset, ROOT + 3);
+        }
+    }
+    assert!(matches!(read(&bytes), Err(SnapshotError::Corrupt)));
+}
+
+#[test]
+fn rejects_set_too_large_for_index_type() {
+    let set: DisjointHashSet<u32> = (0..200).collect();
+    let bytes = snapshot(&set);
+    let read = DisjointHashSet::<u32, RandomState, u8>::read_snapshot(&bytes[..]);
+    assert!(matches!(read, Err(SnapshotError::Corrupt)));
+}
+
+#[test]
+fn rejects_wrong_number_of_sets() {
+    let mut bytes = snapshot(&pair());
+    patch(&mut bytes, NODES - 8, 3);
+    assert!(matches!(read(&bytes), Err(SnapshotError::Corrupt)));
+}
+
+#[test]
+fn rejects_rank_too_large_to_union() {
+    let mut set = DisjointHashSet::<u32>::with_union_by(UnionBy::Rank);
+    set.union(1, 2);
+    set.insert(3);
+    let mut bytes = snapshot(&set);
+    for i in 0..3 {
+        bytes[NODES + i * (NODE + 1) + NODE] = u8::MAX;
+    }
+    fix_checksum(&mut bytes);
+    assert!(matches!(read(&bytes), Err(SnapshotError::Corrupt)));
+}